@@ -0,0 +1,324 @@
+use crate::{
+    base_url::BasePath,
+    command::{Destination, PLACEHOLDER},
+    commands::{AliasAndCommand, AliasMap, Resolution},
+    launcher::{self, LauncherFormat},
+    reload::Reloadable,
+};
+use rocket::http::ContentType;
+use rocket::response::status::{BadRequest, NotFound};
+use rocket::serde::json::Json;
+use rocket::{Route, State};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A command as exposed to external tools. Nested commands are listed separately with their full
+/// alias path (e.g. `aoc/j`) as the alias.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CommandInfo {
+    alias: String,
+    additional_aliases: Vec<String>,
+    description: String,
+    base_url: String,
+    search_template: Option<String>,
+    tags: Vec<String>,
+    notes: Option<String>,
+    icon: Option<String>,
+}
+
+impl CommandInfo {
+    fn new(alias: String, bm: &AliasAndCommand) -> Self {
+        let command = bm.command();
+        Self {
+            alias,
+            additional_aliases: bm.additional_aliases().to_vec(),
+            description: command.short_description(),
+            base_url: command.get_redirect_url(""),
+            search_template: command.get_search_template(PLACEHOLDER),
+            tags: bm.tags().to_vec(),
+            notes: bm.notes().map(str::to_string),
+            icon: bm.icon().map(str::to_string),
+        }
+    }
+}
+
+/// Lists every command, including nested ones, sorted by alias with nested commands following
+/// their parent.
+pub fn command_infos(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+) -> Vec<CommandInfo> {
+    let mut infos = Vec::new();
+    crate::commands::walk(alias_to_bookmark_map, &mut |path, _, bm| {
+        infos.push(CommandInfo::new(path.to_string(), bm))
+    });
+    infos
+}
+
+/// An alias matching an autocomplete prefix.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AliasMatch {
+    alias: String,
+    description: String,
+    url: String,
+}
+
+/// Lists the aliases starting with `prefix`, exact match first.
+pub fn alias_matches(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+    prefix: &str,
+) -> Vec<AliasMatch> {
+    crate::commands::find_aliases(alias_to_bookmark_map, prefix)
+        .into_iter()
+        .map(|(alias, command)| AliasMatch {
+            alias,
+            description: command.short_description(),
+            url: command.get_redirect_url(""),
+        })
+        .collect()
+}
+
+/// Where a search would send the user, for tools that want to open the url themselves.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ResolveResponse {
+    url: String,
+    alias: String,
+    /// `direct` if the search started with an alias, or `default` if it fell back to the default
+    /// alias.
+    mode: &'static str,
+    /// `get` for a plain redirect, or `post` if `fields` must be submitted to the url.
+    method: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<HashMap<String, String>>,
+}
+
+impl From<Resolution> for ResolveResponse {
+    fn from(resolution: Resolution) -> Self {
+        let mode = if resolution.used_default {
+            "default"
+        } else {
+            "direct"
+        };
+        let (url, method, fields) = match resolution.destination {
+            Destination::Redirect(url) => (url, "get", None),
+            Destination::Post { url, fields } => (url, "post", Some(fields)),
+        };
+        Self {
+            url,
+            alias: resolution.alias,
+            mode,
+            method,
+            fields,
+        }
+    }
+}
+
+/// The body of an error response.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ApiError {
+    error: String,
+}
+
+fn not_found(error: String) -> NotFound<Json<ApiError>> {
+    NotFound(Json(ApiError { error }))
+}
+
+#[get("/commands")]
+fn commands(alias_map: &State<AliasMap>) -> Json<Vec<CommandInfo>> {
+    let alias_to_bookmark_map = alias_map.load();
+    Json(command_infos(&alias_to_bookmark_map))
+}
+
+#[get("/aliases?<prefix>")]
+fn aliases(prefix: Option<&str>, alias_map: &State<AliasMap>) -> Json<Vec<AliasMatch>> {
+    let alias_to_bookmark_map = alias_map.load();
+    Json(alias_matches(
+        &alias_to_bookmark_map,
+        prefix.unwrap_or_default(),
+    ))
+}
+
+#[get("/resolve?<q>&<default>")]
+fn resolve(
+    q: &str,
+    default: Option<&str>,
+    alias_map: &State<AliasMap>,
+    default_alias: &State<Reloadable<String>>,
+    base_path: &State<BasePath>,
+) -> Result<Json<ResolveResponse>, NotFound<Json<ApiError>>> {
+    let alias_to_bookmark_map = alias_map.load();
+    let default_alias = default_alias.load();
+    let default_alias = default.unwrap_or(&default_alias);
+    let resolution = crate::commands::resolve(&alias_to_bookmark_map, q, default_alias)
+        .ok_or_else(|| {
+            not_found(format!(
+                "Default search engine alias '{}' was not found!",
+                default_alias
+            ))
+        })?;
+    if let Some(alias) = &resolution.unknown_alias {
+        return Err(not_found(format!(
+            "{} is not a valid command alias under {}",
+            alias, resolution.alias
+        )));
+    }
+    let mut response = ResolveResponse::from(resolution);
+    response.url = base_path.prefix(&response.url);
+    Ok(Json(response))
+}
+
+#[get("/launcher-export?<format>")]
+fn launcher_export(
+    format: &str,
+    alias_map: &State<AliasMap>,
+) -> Result<(ContentType, String), BadRequest<String>> {
+    let alias_to_bookmark_map = alias_map.load();
+    let format: LauncherFormat = format.parse().map_err(BadRequest)?;
+    Ok((
+        format.content_type(),
+        launcher::launcher_export(&alias_to_bookmark_map, format),
+    ))
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![commands, aliases, resolve, launcher_export]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Status;
+
+    #[test]
+    fn test_command_infos() {
+        let map = crate::tests::sample_map();
+        let infos = command_infos(&map);
+        assert_eq!(
+            infos,
+            vec![
+                CommandInfo {
+                    alias: "g".to_string(),
+                    additional_aliases: vec!["google".to_string()],
+                    description: "Search google".to_string(),
+                    base_url: "https://www.google.com".to_string(),
+                    search_template: Some("https://www.google.com/search?q={}".to_string()),
+                    tags: vec!["search".to_string()],
+                    notes: None,
+                    icon: None,
+                },
+                CommandInfo {
+                    alias: "pi".to_string(),
+                    additional_aliases: vec![],
+                    description: "Pi, pages".to_string(),
+                    base_url: "http://pi".to_string(),
+                    search_template: None,
+                    tags: vec![],
+                    notes: None,
+                    icon: None,
+                },
+                CommandInfo {
+                    alias: "pi/j".to_string(),
+                    additional_aliases: vec!["jelly".to_string()],
+                    description: "Jellyfin".to_string(),
+                    base_url: "http://pi:8096".to_string(),
+                    search_template: None,
+                    tags: vec!["media".to_string()],
+                    notes: Some("Runs on the pi".to_string()),
+                    icon: Some("J".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alias_matches() {
+        let map = AliasAndCommand::create_alias_to_bookmark_map_from_str(
+            "- {alias: gh, description: GitHub, url: https://github.com}\n- {alias: g, description: Google, url: https://google.com}\n",
+        );
+        assert_eq!(
+            alias_matches(&map, "g"),
+            vec![
+                AliasMatch {
+                    alias: "g".to_string(),
+                    description: "Google".to_string(),
+                    url: "https://google.com".to_string(),
+                },
+                AliasMatch {
+                    alias: "gh".to_string(),
+                    description: "GitHub".to_string(),
+                    url: "https://github.com".to_string(),
+                },
+            ]
+        );
+        assert!(alias_matches(&map, "x").is_empty());
+
+        let map = crate::tests::sample_map();
+        assert_eq!(
+            alias_matches(&map, "pi/j"),
+            vec![
+                AliasMatch {
+                    alias: "pi/j".to_string(),
+                    description: "Jellyfin".to_string(),
+                    url: "http://pi:8096".to_string(),
+                },
+                AliasMatch {
+                    alias: "pi/jelly".to_string(),
+                    description: "Jellyfin".to_string(),
+                    url: "http://pi:8096".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_response() {
+        let map = AliasAndCommand::create_alias_to_bookmark_map_from_str(
+            "- {alias: g, description: g, url: www.g.com, command: \"www.g.com/?q={}\"}\n- {alias: p, description: p, url: www.p.com, command: www.p.com/search, method: post, fields: {q: \"{}\"}}\n",
+        );
+
+        let response: ResolveResponse = crate::commands::resolve(&map, "g rust", "g")
+            .unwrap()
+            .into();
+        assert_eq!(
+            response,
+            ResolveResponse {
+                url: "www.g.com/?q=rust".to_string(),
+                alias: "g".to_string(),
+                mode: "direct",
+                method: "get",
+                fields: None,
+            }
+        );
+
+        let response: ResolveResponse = crate::commands::resolve(&map, "rust", "g").unwrap().into();
+        assert_eq!(response.mode, "default");
+
+        let response: ResolveResponse = crate::commands::resolve(&map, "p rust", "g")
+            .unwrap()
+            .into();
+        assert_eq!(response.method, "post");
+        assert_eq!(response.fields.unwrap().get("q"), Some(&"rust".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_route() {
+        let client = crate::tests::client();
+
+        let response = client.get("/api/v1/resolve?q=pi/j").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value = response.into_json().unwrap();
+        assert_eq!(body["url"], "http://pi:8096");
+
+        let response = client.get("/api/v1/resolve?q=pi/zzz").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        let body: serde_json::Value = response.into_json().unwrap();
+        assert_eq!(body["error"], "zzz is not a valid command alias under pi");
+
+        let response = client
+            .get("/api/v1/resolve?q=hello&default=missing")
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+    }
+}
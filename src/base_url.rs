@@ -0,0 +1,200 @@
+use rocket::request::{FromRequest, Outcome, Request};
+use std::net::Ipv6Addr;
+
+/// The path prefix the server is served under (e.g. `/bl`), or empty when it is served from the
+/// root. Links in pages and redirects to local urls are prefixed with it.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct BasePath(String);
+
+impl BasePath {
+    pub fn new(path: &str) -> Result<Self, String> {
+        let path = path.trim_end_matches('/');
+        if !path.is_empty() && !path.starts_with('/') {
+            return Err(format!("Base path {} must start with /", path));
+        }
+        if !path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/-_.".contains(c))
+        {
+            return Err(format!(
+                "Base path {} may only contain letters, digits, and -_./",
+                path
+            ));
+        }
+        Ok(Self(path.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Where routes are mounted, e.g. `/bl` or `/`.
+    pub fn mount_point(&self, path: &str) -> String {
+        match format!("{}{}", self.0, path) {
+            mount_point if mount_point.is_empty() => "/".to_string(),
+            mount_point => mount_point,
+        }
+    }
+
+    /// Prefixes local urls (`/help`) with the base path. Other urls are returned unchanged.
+    pub fn prefix(&self, url: &str) -> String {
+        if url.starts_with('/') && !url.starts_with("//") {
+            format!("{}{}", self.0, url)
+        } else {
+            url.to_string()
+        }
+    }
+}
+
+/// The scheme, host, and base path the current request was made to (e.g.
+/// `https://brunnylol.jrodal.com`), for pages that need absolute urls back to this instance. Behind
+/// a reverse proxy, the scheme is taken from `X-Forwarded-Proto`. Both headers come from the client,
+/// so anything but `http`/`https` and a hostname with an optional port is ignored.
+#[derive(Debug, PartialEq)]
+pub struct BaseUrl(pub String);
+
+impl BaseUrl {
+    fn new(scheme: Option<&str>, host: Option<&str>, base_path: &BasePath) -> Self {
+        let scheme = match scheme.map(str::to_ascii_lowercase).as_deref() {
+            Some("https") => "https",
+            _ => "http",
+        };
+        let host = host
+            .filter(|host| is_valid_host(host))
+            .unwrap_or("localhost");
+        Self(format!("{}://{}{}", scheme, host, base_path.as_str()))
+    }
+}
+
+/// Accepts a hostname or IP address (`[::1]` for IPv6), optionally followed by `:port`.
+fn is_valid_host(host: &str) -> bool {
+    let (name, port) = match host.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((address, port)) if address.parse::<Ipv6Addr>().is_ok() => (None, port),
+            _ => return false,
+        },
+        None => match host.find(':') {
+            Some(index) => (Some(&host[..index]), &host[index..]),
+            None => (Some(host), ""),
+        },
+    };
+    let valid_port = port.is_empty()
+        || port
+            .strip_prefix(':')
+            .is_some_and(|port| port.parse::<u16>().is_ok());
+    valid_port && name.is_none_or(is_valid_hostname)
+}
+
+fn is_valid_hostname(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BaseUrl {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let scheme = request.headers().get_one("X-Forwarded-Proto");
+        let host = request.host().map(|host| host.to_string());
+        let base_path = request
+            .rocket()
+            .state::<BasePath>()
+            .cloned()
+            .unwrap_or_default();
+        Outcome::Success(BaseUrl::new(scheme, host.as_deref(), &base_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_url() {
+        assert_eq!(
+            BaseUrl::new(
+                Some("https"),
+                Some("brunnylol.jrodal.com"),
+                &BasePath::default()
+            ),
+            BaseUrl("https://brunnylol.jrodal.com".to_string())
+        );
+        assert_eq!(
+            BaseUrl::new(None, Some("localhost:8000"), &BasePath::default()),
+            BaseUrl("http://localhost:8000".to_string())
+        );
+        assert_eq!(
+            BaseUrl::new(None, None, &BasePath::new("/bl").unwrap()),
+            BaseUrl("http://localhost/bl".to_string())
+        );
+    }
+
+    #[test]
+    fn test_untrusted_headers() {
+        assert_eq!(
+            BaseUrl::new(
+                Some("</script><script>alert(1)</script>"),
+                Some("example.com"),
+                &BasePath::default()
+            ),
+            BaseUrl("http://example.com".to_string())
+        );
+        assert_eq!(
+            BaseUrl::new(Some("javascript"), None, &BasePath::default()),
+            BaseUrl("http://localhost".to_string())
+        );
+        assert_eq!(
+            BaseUrl::new(Some("HTTPS"), Some("evil.com/<x>"), &BasePath::default()),
+            BaseUrl("https://localhost".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_valid_host() {
+        assert!(is_valid_host("brunnylol.jrodal.com"));
+        assert!(is_valid_host("localhost:8000"));
+        assert!(is_valid_host("127.0.0.1:8000"));
+        assert!(is_valid_host("[::1]:8000"));
+        assert!(is_valid_host("[::1]"));
+        assert!(!is_valid_host(""));
+        assert!(!is_valid_host("example.com:"));
+        assert!(!is_valid_host("example.com:99999"));
+        assert!(!is_valid_host("exa\"mple.com"));
+        assert!(!is_valid_host("-example.com"));
+        assert!(!is_valid_host("[not-ipv6]:80"));
+        assert!(!is_valid_host("user@example.com"));
+    }
+
+    #[test]
+    fn test_base_path() {
+        assert_eq!(BasePath::new("/bl/"), Ok(BasePath("/bl".to_string())));
+        assert_eq!(BasePath::new("/"), Ok(BasePath::default()));
+        assert!(BasePath::new("bl").is_err());
+        assert!(BasePath::new("/b\"<l").is_err());
+    }
+
+    #[test]
+    fn test_mount_point() {
+        assert_eq!(BasePath::default().mount_point(""), "/");
+        assert_eq!(BasePath::default().mount_point("/api/v1"), "/api/v1");
+        let base_path = BasePath::new("/bl").unwrap();
+        assert_eq!(base_path.mount_point(""), "/bl");
+        assert_eq!(base_path.mount_point("/api/v1"), "/bl/api/v1");
+    }
+
+    #[test]
+    fn test_prefix() {
+        let base_path = BasePath::new("/bl").unwrap();
+        assert_eq!(base_path.prefix("/help"), "/bl/help");
+        assert_eq!(base_path.prefix("https://google.com"), "https://google.com");
+        assert_eq!(base_path.prefix("//google.com"), "//google.com");
+        assert_eq!(BasePath::default().prefix("/help"), "/help");
+    }
+}
@@ -0,0 +1,79 @@
+use serde::Serialize;
+
+const DEFAULT_INSTANCE_NAME: &str = "Brunnylol";
+
+/// How this instance presents itself in the templates.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct Branding {
+    pub instance_name: String,
+    pub logo_url: Option<String>,
+    pub accent_color: Option<String>,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            instance_name: DEFAULT_INSTANCE_NAME.to_string(),
+            logo_url: None,
+            accent_color: None,
+        }
+    }
+}
+
+impl Branding {
+    /// Creates the branding, falling back to the defaults for anything that isn't set. The accent
+    /// color is written into a stylesheet, so only hex colors (`#1a73e8`) and color names (`teal`)
+    /// are accepted.
+    pub fn new(
+        instance_name: Option<&str>,
+        logo_url: Option<&str>,
+        accent_color: Option<&str>,
+    ) -> Result<Self, String> {
+        if let Some(color) = accent_color {
+            if !is_valid_color(color) {
+                return Err(format!("Invalid accent color: {}", color));
+            }
+        }
+        Ok(Self {
+            instance_name: instance_name.unwrap_or(DEFAULT_INSTANCE_NAME).to_string(),
+            logo_url: logo_url.map(str::to_string),
+            accent_color: accent_color.map(str::to_string),
+        })
+    }
+}
+
+fn is_valid_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => {
+            matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        assert_eq!(Branding::new(None, None, None), Ok(Branding::default()));
+    }
+
+    #[test]
+    fn test_custom_branding() {
+        let branding =
+            Branding::new(Some("Family links"), Some("/logo.png"), Some("#1a73e8")).unwrap();
+        assert_eq!(branding.instance_name, "Family links".to_string());
+        assert_eq!(branding.logo_url, Some("/logo.png".to_string()));
+        assert_eq!(branding.accent_color, Some("#1a73e8".to_string()));
+    }
+
+    #[test]
+    fn test_accent_colors() {
+        assert!(Branding::new(None, None, Some("teal")).is_ok());
+        assert!(Branding::new(None, None, Some("#abc")).is_ok());
+        assert!(Branding::new(None, None, Some("#abcg")).is_err());
+        assert!(Branding::new(None, None, Some("red;}body{display:none")).is_err());
+    }
+}
@@ -49,4 +49,10 @@ mod tests {
             "www.example.com".to_string()
         );
     }
+
+    #[test]
+    fn test_no_search_template() {
+        let bookmark = BookmarkCommand::new("www.example.com", "a test website");
+        assert_eq!(bookmark.get_search_template("%s"), None);
+    }
 }
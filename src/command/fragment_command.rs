@@ -0,0 +1,127 @@
+use super::{Command, Destination};
+use crate::commands::AliasToBookmarkMap;
+
+/// A struct that wraps another command and appends a fixed fragment to every URL it resolves to.
+/// If the resolved URL already has a fragment, the fixed fragment is joined to it with `&`.
+pub struct FragmentCommand {
+    command: Box<dyn Command>,
+    fragment: String,
+}
+
+impl FragmentCommand {
+    fn append_fragment(&self, url: String) -> String {
+        match url.find('#') {
+            Some(index) if index == url.len() - 1 => format!("{}{}", url, self.fragment),
+            Some(_) => format!("{}&{}", url, self.fragment),
+            None => format!("{}#{}", url, self.fragment),
+        }
+    }
+}
+
+impl Command for FragmentCommand {
+    fn description(&self) -> String {
+        self.command.description()
+    }
+
+    fn short_description(&self) -> String {
+        self.command.short_description()
+    }
+
+    fn get_redirect_url(&self, query: &str) -> String {
+        self.append_fragment(self.command.get_redirect_url(query))
+    }
+
+    fn get_destination(&self, query: &str) -> Destination {
+        match self.command.get_destination(query) {
+            Destination::Redirect(url) => Destination::Redirect(self.append_fragment(url)),
+            post => post,
+        }
+    }
+
+    fn get_search_template(&self, placeholder: &str) -> Option<String> {
+        self.command
+            .get_search_template(placeholder)
+            .map(|url| self.append_fragment(url))
+    }
+
+    fn find_unknown_alias(&self, query: &str) -> Option<String> {
+        self.command.find_unknown_alias(query)
+    }
+
+    fn nested_commands(&self) -> Option<&AliasToBookmarkMap> {
+        self.command.nested_commands()
+    }
+}
+
+impl FragmentCommand {
+    pub fn new(command: Box<dyn Command>, fragment: &str) -> Self {
+        Self {
+            command,
+            fragment: fragment.trim_start_matches('#').to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::{bookmark_command::BookmarkCommand, templated_command::TemplatedCommand};
+
+    use super::*;
+
+    #[test]
+    fn test_description() {
+        let command = FragmentCommand::new(
+            Box::new(BookmarkCommand::new("www.example.com", "a test website")),
+            "view=list",
+        );
+        assert_eq!(command.description(), "a test website".to_string());
+    }
+
+    #[test]
+    fn test_bookmark_fragment() {
+        let command = FragmentCommand::new(
+            Box::new(BookmarkCommand::new("www.example.com", "a test website")),
+            "#view=list",
+        );
+        assert_eq!(
+            command.get_redirect_url(""),
+            "www.example.com#view=list".to_string()
+        );
+    }
+
+    #[test]
+    fn test_templated_fragment() {
+        let command = FragmentCommand::new(
+            Box::new(TemplatedCommand::new(
+                "www.example.com",
+                "www.example.com/search?q={}",
+                "a test website",
+            )),
+            "view=list",
+        );
+        assert_eq!(
+            command.get_redirect_url("hello world"),
+            "www.example.com/search?q=hello%20world#view=list".to_string()
+        );
+    }
+
+    #[test]
+    fn test_existing_fragment() {
+        let command = FragmentCommand::new(
+            Box::new(TemplatedCommand::new(
+                "www.example.com/#",
+                "www.example.com/#q={}",
+                "a test website",
+            )),
+            "view=list",
+        );
+        assert_eq!(
+            command.get_redirect_url(""),
+            "www.example.com/#view=list".to_string()
+        );
+        assert_eq!(
+            command.get_redirect_url("hello"),
+            "www.example.com/#q=hello&view=list".to_string()
+        );
+    }
+}
@@ -1,8 +1,53 @@
+use crate::commands::AliasToBookmarkMap;
+use std::collections::HashMap;
+
 pub mod bookmark_command;
+pub mod fragment_command;
 pub mod nested_command;
+pub mod post_command;
 pub mod templated_command;
 
+/// Stands in for the query in command templates and form fields.
+pub const PLACEHOLDER: &str = "{}";
+
+/// Where the user should be sent after a command has been resolved.
+pub enum Destination {
+    /// Navigate to the url with a regular redirect.
+    Redirect(String),
+    /// Submit the form fields to the url with a POST request.
+    Post {
+        url: String,
+        fields: HashMap<String, String>,
+    },
+}
+
 pub trait Command: Send + Sync {
     fn description(&self) -> String;
     fn get_redirect_url(&self, query: &str) -> String;
+
+    /// Returns the description of this command alone, without anything it is made up of.
+    fn short_description(&self) -> String {
+        self.description()
+    }
+
+    fn get_destination(&self, query: &str) -> Destination {
+        Destination::Redirect(self.get_redirect_url(query))
+    }
+
+    /// Returns the url this command searches with, with `placeholder` in place of the query, or
+    /// None if the command doesn't take a query that can be expressed as a single url.
+    fn get_search_template(&self, _placeholder: &str) -> Option<String> {
+        None
+    }
+
+    /// Returns the nested alias at the start of the query that doesn't match any nested command,
+    /// or None if the query resolves. Queries with an unknown nested alias go to the bookmark.
+    fn find_unknown_alias(&self, _query: &str) -> Option<String> {
+        None
+    }
+
+    /// Returns the commands nested under this command, keyed by each of their aliases.
+    fn nested_commands(&self) -> Option<&AliasToBookmarkMap> {
+        None
+    }
 }
@@ -1,45 +1,80 @@
-use std::collections::HashMap;
-
-use super::Command;
+use super::{Command, Destination};
+use crate::commands::AliasToBookmarkMap;
 
 pub struct NestedCommand {
     bookmark: String,
-    commands: HashMap<String, Box<dyn Command>>,
+    commands: AliasToBookmarkMap,
     description: String,
 }
 
 impl Command for NestedCommand {
     fn description(&self) -> String {
         let mut description = self.description.clone();
-        for (alias, command) in self.commands.iter() {
-            description.push_str(&format!("|{}: {}", alias, command.description()));
+        for (alias, bm) in self.commands.iter() {
+            if alias != bm.alias() {
+                continue;
+            }
+            let aliases = std::iter::once(alias)
+                .chain(bm.additional_aliases().iter())
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            description.push_str(&format!("|{}: {}", aliases, bm.command().description()));
         }
         description
     }
 
+    fn short_description(&self) -> String {
+        self.description.clone()
+    }
+
     fn get_redirect_url(&self, query: &str) -> String {
-        let mut splitted = query.splitn(2, " ");
-        let alias = splitted.next().expect("Expected alias");
+        match self.find_command(query) {
+            Ok(Some((command, nested_query))) => command.get_redirect_url(nested_query),
+            Ok(None) | Err(_) => self.bookmark.clone(),
+        }
+    }
 
-        if alias.is_empty() {
-            return self.bookmark.clone();
+    fn get_destination(&self, query: &str) -> Destination {
+        match self.find_command(query) {
+            Ok(Some((command, nested_query))) => command.get_destination(nested_query),
+            Ok(None) | Err(_) => Destination::Redirect(self.bookmark.clone()),
         }
+    }
 
-        let nested_query = splitted.next().unwrap_or_default();
+    fn find_unknown_alias(&self, query: &str) -> Option<String> {
+        match self.find_command(query) {
+            Ok(Some((command, nested_query))) => command.find_unknown_alias(nested_query),
+            Ok(None) => None,
+            Err(alias) => Some(alias.to_string()),
+        }
+    }
 
-        self.commands
-            .get(alias)
-            .expect(&format!("{} is not a valid command alias", alias))
-            .get_redirect_url(nested_query)
+    fn nested_commands(&self) -> Option<&AliasToBookmarkMap> {
+        Some(&self.commands)
     }
 }
 
 impl NestedCommand {
-    pub fn new(
-        bookmark: &str,
-        commands: HashMap<String, Box<dyn Command>>,
-        description: &str,
-    ) -> Self {
+    /// Splits the nested alias off of the query, returning the matching command and the rest of
+    /// the query. Returns None if no nested alias was provided, or the alias if it doesn't match
+    /// any nested command.
+    fn find_command<'a>(&self, query: &'a str) -> Result<Option<(&dyn Command, &'a str)>, &'a str> {
+        let mut splitted = query.splitn(2, ' ');
+        let alias = splitted.next().expect("Expected alias");
+
+        if alias.is_empty() {
+            return Ok(None);
+        }
+
+        let nested_query = splitted.next().unwrap_or_default();
+        match self.commands.get(alias) {
+            Some(bm) => Ok(Some((bm.command(), nested_query))),
+            None => Err(alias),
+        }
+    }
+
+    pub fn new(bookmark: &str, commands: AliasToBookmarkMap, description: &str) -> Self {
         Self {
             bookmark: bookmark.to_string(),
             commands,
@@ -49,32 +84,65 @@ impl NestedCommand {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
-    use crate::command::{bookmark_command::BookmarkCommand, templated_command::TemplatedCommand};
+    use crate::command::{
+        bookmark_command::BookmarkCommand, post_command::PostCommand,
+        templated_command::TemplatedCommand,
+    };
 
     use super::*;
+    use crate::commands::AliasAndCommand;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn insert(commands: &mut AliasToBookmarkMap, alias: &str, command: Arc<dyn Command>) {
+        commands.insert(
+            alias.to_string(),
+            Arc::new(AliasAndCommand::new(alias, command)),
+        );
+    }
 
     #[allow(dead_code)]
     fn create_nested_command(should_recurse: bool) -> NestedCommand {
-        let mut commands: HashMap<String, Box<dyn Command>> = HashMap::new();
+        let mut commands = AliasToBookmarkMap::new();
         // a single character should work
-        commands.insert(
-            "t".to_string(),
-            Box::new(TemplatedCommand::new(
+        insert(
+            &mut commands,
+            "t",
+            Arc::new(TemplatedCommand::new(
                 "www.template.com",
                 "www.template.com/{}",
                 "templated command",
             )),
         );
         // an entire word should work as well
-        commands.insert(
-            "bookmark".to_string(),
-            Box::new(BookmarkCommand::new("www.bookmark.com", "bookmark command")),
+        insert(
+            &mut commands,
+            "bookmark",
+            Arc::new(BookmarkCommand::new("www.bookmark.com", "bookmark command")),
+        );
+
+        let mut fields = HashMap::new();
+        fields.insert("q".to_string(), "{}".to_string());
+        insert(
+            &mut commands,
+            "p",
+            Arc::new(PostCommand::new(
+                "www.post.com",
+                "www.post.com/search",
+                fields,
+                "post command",
+            )),
         );
 
         if should_recurse {
             // arbitrary nesting should be possible
-            commands.insert("nested".to_string(), Box::new(create_nested_command(false)));
+            insert(
+                &mut commands,
+                "nested",
+                Arc::new(create_nested_command(false)),
+            );
         }
 
         NestedCommand::new("www.example.com", commands, "a test website")
@@ -88,6 +156,13 @@ mod tests {
         assert_eq!(description.contains("|nested: a test website"), true);
         assert_eq!(description.contains("|bookmark: bookmark command"), true);
         assert_eq!(description.contains("|t: templated command"), true);
+        assert_eq!(description.contains("|p: post command"), true);
+    }
+
+    #[test]
+    fn test_short_description() {
+        let command = create_nested_command(true);
+        assert_eq!(command.short_description(), "a test website".to_string());
     }
 
     #[test]
@@ -149,4 +224,35 @@ mod tests {
             "www.template.com/hello%20world".to_string()
         );
     }
+
+    #[test]
+    fn test_unknown_alias() {
+        let command = create_nested_command(true);
+        assert_eq!(
+            command.get_redirect_url("zzz hello"),
+            "www.example.com".to_string()
+        );
+        assert_eq!(
+            command.find_unknown_alias("zzz hello"),
+            Some("zzz".to_string())
+        );
+        assert_eq!(
+            command.find_unknown_alias("nested zzz"),
+            Some("zzz".to_string())
+        );
+        assert_eq!(command.find_unknown_alias("nested t hello"), None);
+        assert_eq!(command.find_unknown_alias(""), None);
+    }
+
+    #[test]
+    fn test_nested_post_command() {
+        let command = create_nested_command(true);
+        match command.get_destination("nested p hello world") {
+            Destination::Post { url, fields } => {
+                assert_eq!(url, "www.post.com/search".to_string());
+                assert_eq!(fields.get("q"), Some(&"hello world".to_string()));
+            }
+            Destination::Redirect(_) => panic!("Expected a post"),
+        }
+    }
 }
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use super::{Command, Destination, PLACEHOLDER};
+
+/// A struct that represents a command that submits a form to a pre-defined URL when executed.
+/// Every `{}` in a field value is replaced by the query.
+pub struct PostCommand {
+    bookmark: String,
+    action: String,
+    fields: HashMap<String, String>,
+    description: String,
+}
+
+impl Command for PostCommand {
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    /// A plain redirect can't submit the form, so the only url this command can be reached at is
+    /// the bookmark.
+    fn get_redirect_url(&self, _query: &str) -> String {
+        self.bookmark.clone()
+    }
+
+    fn get_destination(&self, query: &str) -> Destination {
+        if query.is_empty() {
+            return Destination::Redirect(self.bookmark.clone());
+        }
+
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, value)| (name.clone(), value.replace(PLACEHOLDER, query)))
+            .collect();
+        Destination::Post {
+            url: self.action.clone(),
+            fields,
+        }
+    }
+}
+
+impl PostCommand {
+    pub fn new(
+        bookmark: &str,
+        action: &str,
+        fields: HashMap<String, String>,
+        description: &str,
+    ) -> Self {
+        Self {
+            bookmark: bookmark.to_string(),
+            action: action.to_string(),
+            fields,
+            description: description.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_post_command() -> PostCommand {
+        let mut fields = HashMap::new();
+        fields.insert("q".to_string(), "{}".to_string());
+        fields.insert("type".to_string(), "search".to_string());
+        PostCommand::new(
+            "www.example.com",
+            "www.example.com/search",
+            fields,
+            "a test website",
+        )
+    }
+
+    #[test]
+    fn test_description() {
+        let command = create_post_command();
+        assert_eq!(command.description(), "a test website".to_string());
+    }
+
+    #[test]
+    fn test_empty_query_redirect() {
+        let command = create_post_command();
+        match command.get_destination("") {
+            Destination::Redirect(url) => assert_eq!(url, "www.example.com".to_string()),
+            Destination::Post { .. } => panic!("Expected a redirect"),
+        }
+    }
+
+    #[test]
+    fn test_redirect_url_is_bookmark() {
+        let command = create_post_command();
+        assert_eq!(command.get_redirect_url(""), "www.example.com".to_string());
+        assert_eq!(
+            command.get_redirect_url("hello world"),
+            "www.example.com".to_string()
+        );
+        assert_eq!(command.get_search_template("%s"), None);
+    }
+
+    #[test]
+    fn test_non_empty_query_post() {
+        let command = create_post_command();
+        match command.get_destination("hello world") {
+            Destination::Post { url, fields } => {
+                assert_eq!(url, "www.example.com/search".to_string());
+                assert_eq!(fields.get("q"), Some(&"hello world".to_string()));
+                assert_eq!(fields.get("type"), Some(&"search".to_string()));
+            }
+            Destination::Redirect(_) => panic!("Expected a post"),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use rocket::http::RawStr;
 
-use super::Command;
+use super::{Command, PLACEHOLDER};
 
 struct TemplatedString {
     template: String,
@@ -54,13 +54,17 @@ impl Command for TemplatedCommand {
             query => self.template.replace(query),
         }
     }
+
+    fn get_search_template(&self, placeholder: &str) -> Option<String> {
+        Some(self.template.replace(placeholder))
+    }
 }
 
 impl TemplatedCommand {
     pub fn new(bookmark: &str, template: &str, description: &str) -> Self {
         Self {
             bookmark: bookmark.to_string(),
-            template: TemplatedString::new(template, "{}"),
+            template: TemplatedString::new(template, PLACEHOLDER),
             description: description.to_string(),
             encode_query: true,
         }
@@ -121,4 +125,37 @@ mod tests {
             "www.example.com/%s".to_string()
         );
     }
+
+    #[test]
+    fn test_fragment_query_redirect() {
+        let command =
+            TemplatedCommand::new("www.example.com", "www.example.com/#q={}", "a test website");
+        assert_eq!(
+            command.get_redirect_url("a&b=c #d"),
+            "www.example.com/#q=a%26b%3Dc%20%23d".to_string()
+        );
+    }
+
+    #[test]
+    fn test_fragment_route_redirect() {
+        let command = TemplatedCommand::new(
+            "www.example.com",
+            "www.example.com/#/search/{}",
+            "a test website",
+        );
+        assert_eq!(
+            command.get_redirect_url("hello/world"),
+            "www.example.com/#/search/hello%2Fworld".to_string()
+        );
+    }
+
+    #[test]
+    fn test_search_template() {
+        let command =
+            TemplatedCommand::new("www.example.com", "www.example.com/{}", "a test website");
+        assert_eq!(
+            command.get_search_template("%s"),
+            Some("www.example.com/%s".to_string())
+        );
+    }
 }
@@ -1,28 +1,174 @@
 use crate::{
     command::{
-        bookmark_command::BookmarkCommand, nested_command::NestedCommand,
-        templated_command::TemplatedCommand, Command,
+        bookmark_command::BookmarkCommand, fragment_command::FragmentCommand,
+        nested_command::NestedCommand, post_command::PostCommand,
+        templated_command::TemplatedCommand, Command, Destination,
     },
-    yml_settings::YmlSettings,
+    reload::Reloadable,
+    validate::{self, ValidationReport},
+    yml_settings::{HttpMethod, YmlSettings},
 };
 use std::collections::HashMap;
+use std::sync::Arc;
 
-const DEFAULT_CONFIG_FILE: &'static str = "commands.yml";
+pub const DEFAULT_CONFIG_FILE: &str = "commands.yml";
+const NESTED_ALIAS_SEPARATOR: char = '/';
+
+/// Splits a search into the alias and the query that should be passed to its command.
+/// Nested aliases can be written as a path (`pi/j query`), which is equivalent to separating
+/// them with spaces (`pi j query`). Only aliases with nested commands are split this way, so
+/// searches like `r/rust` are left alone.
+pub fn split_alias_and_query<'a>(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+    q: &'a str,
+) -> (&'a str, String) {
+    let mut splitted = q.splitn(2, ' ');
+    let alias_path = splitted.next().unwrap_or_default();
+    let query = splitted.next().unwrap_or_default();
+
+    match alias_path.split_once(NESTED_ALIAS_SEPARATOR) {
+        Some((alias, nested_path)) if has_nested_commands(alias_to_bookmark_map, alias) => {
+            let query = nested_path
+                .split(NESTED_ALIAS_SEPARATOR)
+                .chain(std::iter::once(query))
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            (alias, query)
+        }
+        _ => (alias_path, query.to_string()),
+    }
+}
+
+fn has_nested_commands(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+    alias: &str,
+) -> bool {
+    alias_to_bookmark_map
+        .get(alias)
+        .and_then(|bm| bm.command().nested_commands())
+        .is_some_and(|commands| !commands.is_empty())
+}
+
+/// A search that has been resolved to a command.
+pub struct Resolution {
+    /// The alias of the command that handled the search.
+    pub alias: String,
+    /// Whether no alias matched and the search was handled by the default alias instead.
+    pub used_default: bool,
+    /// The nested alias that didn't match any nested command, if any. The destination is then
+    /// the bookmark of the command the nested alias was looked up in.
+    pub unknown_alias: Option<String>,
+    pub destination: Destination,
+}
+
+/// Resolves a search against the alias map. If the search doesn't start with a known alias, the
+/// whole search is passed to the default alias. Returns None if the default alias doesn't exist.
+pub fn resolve(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+    q: &str,
+    default_alias: &str,
+) -> Option<Resolution> {
+    let (alias, query) = split_alias_and_query(alias_to_bookmark_map, q);
+    match alias_to_bookmark_map.get(alias) {
+        Some(bookmark) => Some(Resolution {
+            alias: alias.to_string(),
+            used_default: false,
+            unknown_alias: bookmark.command().find_unknown_alias(&query),
+            destination: bookmark.command().get_destination(&query),
+        }),
+        None => alias_to_bookmark_map
+            .get(default_alias)
+            .map(|bookmark| Resolution {
+                alias: default_alias.to_string(),
+                used_default: true,
+                unknown_alias: bookmark.command().find_unknown_alias(q),
+                destination: bookmark.command().get_destination(q),
+            }),
+    }
+}
+
+/// Visits every command once, under its primary alias, along with its alias path (e.g. `aoc/j`)
+/// and how deeply it is nested. Each level is visited in alphabetical order, and nested commands
+/// directly follow their parent.
+pub fn walk<'a>(
+    alias_to_bookmark_map: &'a AliasToBookmarkMap,
+    visit: &mut impl FnMut(&str, usize, &'a AliasAndCommand),
+) {
+    walk_level(alias_to_bookmark_map, "", 0, visit);
+}
+
+fn walk_level<'a>(
+    alias_to_bookmark_map: &'a AliasToBookmarkMap,
+    parent: &str,
+    depth: usize,
+    visit: &mut impl FnMut(&str, usize, &'a AliasAndCommand),
+) {
+    let mut level: Vec<&AliasAndCommand> = alias_to_bookmark_map
+        .iter()
+        .filter(|(alias, bm)| *alias == bm.alias())
+        .map(|(_, bm)| bm.as_ref())
+        .collect();
+    level.sort_by_key(|bm| bm.alias());
+    for bm in level {
+        let path = format!("{}{}", parent, bm.alias());
+        visit(&path, depth, bm);
+        if let Some(commands) = bm.command().nested_commands() {
+            let parent = format!("{}{}", path, NESTED_ALIAS_SEPARATOR);
+            walk_level(commands, &parent, depth + 1, visit);
+        }
+    }
+}
+
+/// Finds the aliases (including additional aliases) that start with `prefix`. An exact match comes
+/// first, followed by the other matches in alphabetical order. Nested aliases are listed by their
+/// path (e.g. `aoc/j`) once the prefix names their parent followed by a `/`.
+pub fn find_aliases<'a>(
+    alias_to_bookmark_map: &'a AliasToBookmarkMap,
+    prefix: &str,
+) -> Vec<(String, &'a dyn Command)> {
+    let mut matches = Vec::new();
+    walk(alias_to_bookmark_map, &mut |path, _, bm| {
+        let parent = &path[..path.len() - bm.alias().len()];
+        if !prefix.starts_with(parent) {
+            return;
+        }
+        for alias in std::iter::once(&bm.alias).chain(bm.additional_aliases.iter()) {
+            let path = format!("{}{}", parent, alias);
+            if path.starts_with(prefix) {
+                matches.push((path, bm.command()));
+            }
+        }
+    });
+    matches.sort_by(|(a, _), (b, _)| (a != prefix, a).cmp(&(b != prefix, b)));
+    matches
+}
 
 /// AliasAndCommand is an object that holds a command that the user can execute and an alias
-/// that the user can use to reference that command.
+/// that the user can use to reference that command. Any additional aliases reference the same
+/// command.
 pub struct AliasAndCommand {
     alias: String,
-    command: Box<dyn Command>,
+    additional_aliases: Vec<String>,
+    command: Arc<dyn Command>,
+    tags: Vec<String>,
+    notes: Option<String>,
+    icon: Option<String>,
 }
 
 impl From<YmlSettings> for AliasAndCommand {
     fn from(value: YmlSettings) -> Self {
-        let command_box = match (value.command, value.encode, value.nested) {
-            (None, None, None) => {
+        let command_box = match (
+            value.method.unwrap_or_default(),
+            value.command,
+            value.encode,
+            value.nested,
+            value.fields,
+        ) {
+            (HttpMethod::Get, None, None, None, None) => {
                 Box::new(BookmarkCommand::new(&value.url, &value.description)) as Box<dyn Command>
             }
-            (Some(command), maybe_encode, None) => {
+            (HttpMethod::Get, Some(command), maybe_encode, None, None) => {
                 let tc = TemplatedCommand::new(&value.url, &command, &value.description);
                 Box::new(if !maybe_encode.unwrap_or(true) {
                     tc.with_no_query_encode()
@@ -30,55 +176,192 @@ impl From<YmlSettings> for AliasAndCommand {
                     tc
                 })
             }
-            (None, None, Some(nested)) => {
+            (HttpMethod::Get, None, None, Some(nested), None) => {
                 let alias_and_commands =
                     nested.into_iter().map(|settings| settings.into()).collect();
                 let commands = AliasAndCommand::create_alias_to_bookmark_map(alias_and_commands);
                 Box::new(NestedCommand::new(&value.url, commands, &value.description))
             }
+            (HttpMethod::Post, Some(action), None, None, Some(fields)) => Box::new(
+                PostCommand::new(&value.url, &action, fields, &value.description),
+            ),
             _ => panic!("Invalid yaml configuration"),
         };
+        let command_box = match value.fragment {
+            Some(fragment) => Box::new(FragmentCommand::new(command_box, &fragment)),
+            None => command_box,
+        };
         Self {
             alias: value.alias.clone(),
-            command: command_box,
+            additional_aliases: value.aliases.unwrap_or_default(),
+            command: Arc::from(command_box),
+            tags: value.tags.unwrap_or_default(),
+            notes: value.notes,
+            icon: value.icon,
         }
     }
 }
 
 impl AliasAndCommand {
+    /// Wraps a command with no additional aliases or metadata. Used by tests that build commands
+    /// directly.
+    #[cfg(test)]
+    pub fn new(alias: &str, command: Arc<dyn Command>) -> Self {
+        Self {
+            alias: alias.to_string(),
+            additional_aliases: vec![],
+            command,
+            tags: vec![],
+            notes: None,
+            icon: None,
+        }
+    }
+
+    pub fn alias(&self) -> &str {
+        &self.alias
+    }
+
+    pub fn additional_aliases(&self) -> &[String] {
+        &self.additional_aliases
+    }
+
+    pub fn command(&self) -> &dyn Command {
+        self.command.as_ref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
     fn create_alias_to_bookmark_map(
         alias_and_commands: Vec<AliasAndCommand>,
-    ) -> HashMap<String, Box<dyn Command>> {
+    ) -> HashMap<String, Arc<AliasAndCommand>> {
         let mut map = HashMap::new();
         for alias_and_command in alias_and_commands.into_iter() {
-            if map
-                .insert(alias_and_command.alias.clone(), alias_and_command.command)
-                .is_some()
-            {
-                panic!("Duplicate alias: {}", alias_and_command.alias);
+            let alias_and_command = Arc::new(alias_and_command);
+            let aliases = std::iter::once(&alias_and_command.alias)
+                .chain(alias_and_command.additional_aliases.iter());
+            for alias in aliases {
+                if map
+                    .insert(alias.clone(), alias_and_command.clone())
+                    .is_some()
+                {
+                    panic!("Duplicate alias: {}", alias);
+                }
             }
         }
         map
     }
 
-    pub fn get_alias_to_bookmark_map(maybe_yml: Option<&str>) -> HashMap<String, Box<dyn Command>> {
-        let yml = std::fs::read_to_string(maybe_yml.unwrap_or(DEFAULT_CONFIG_FILE))
-            .expect("Could not read file");
+    /// Builds the alias map from the contents of a commands file. Used by tests that need a map
+    /// without writing a file.
+    #[cfg(test)]
+    pub fn create_alias_to_bookmark_map_from_str(
+        yml: &str,
+    ) -> HashMap<String, Arc<AliasAndCommand>> {
         let settings: Vec<YmlSettings> =
-            serde_yaml::from_str(&yml).expect("Invalid yaml configuration");
-        let alias_and_commands = settings.into_iter().map(AliasAndCommand::from).collect();
-        Self::create_alias_to_bookmark_map(alias_and_commands)
+            serde_yaml::from_str(yml).expect("Invalid yaml configuration");
+        Self::create_alias_to_bookmark_map(
+            settings.into_iter().map(AliasAndCommand::from).collect(),
+        )
+    }
+
+    /// Builds the alias map from one or more command files, falling back to the default file if
+    /// none are given. The files are read once and checked first, so the duplicate aliases and
+    /// invalid settings that would make building the map panic are returned as a report instead.
+    /// Lint problems, such as urls without a scheme, are only logged.
+    pub fn get_alias_to_bookmark_map(
+        ymls: &[&str],
+    ) -> Result<AliasToBookmarkMap, ValidationReport> {
+        let ymls = if ymls.is_empty() {
+            &[DEFAULT_CONFIG_FILE][..]
+        } else {
+            ymls
+        };
+        let (settings, report) = validate::load_files(ymls);
+        if !report.can_load() {
+            return Err(report);
+        }
+        for lint in report.lints() {
+            eprintln!("warning: {}", lint);
+        }
+        Ok(Self::create_alias_to_bookmark_map(
+            settings.into_iter().map(AliasAndCommand::from).collect(),
+        ))
     }
 }
 
+/// Maps every alias, including additional aliases, to its command.
+pub type AliasToBookmarkMap = HashMap<String, Arc<AliasAndCommand>>;
+
+/// The alias map shared by every request, replaced as a whole when the commands are reloaded.
+pub type AliasMap = Reloadable<AliasToBookmarkMap>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::temp_file;
 
     #[test]
     fn test_valid_map() {
         // ensure that the map can be constructed
-        let _ = AliasAndCommand::get_alias_to_bookmark_map(None);
+        assert!(AliasAndCommand::get_alias_to_bookmark_map(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_multiple_files() {
+        let file = temp_file(
+            "- {alias: brunnylol-test, description: test, url: https://www.example.com}\n",
+        );
+        let path = file.path();
+        let map =
+            AliasAndCommand::get_alias_to_bookmark_map(&["commands.yml", path.to_str().unwrap()])
+                .unwrap();
+        assert!(map.contains_key("brunnylol-test"));
+        assert!(map.contains_key("g"));
+    }
+
+    #[test]
+    fn test_duplicate_across_files() {
+        let file = temp_file("- {alias: g, description: test, url: https://www.example.com}\n");
+        let path = file.path();
+        let report =
+            AliasAndCommand::get_alias_to_bookmark_map(&["commands.yml", path.to_str().unwrap()])
+                .err()
+                .unwrap();
+        assert!(report.to_string().contains("duplicate alias g"));
+    }
+
+    #[test]
+    fn test_lints_do_not_block_loading() {
+        let file = temp_file(
+            "- {alias: r/rust, description: rust, url: www.rust-lang.org, command: \"www.rust-lang.org/?q={}\"}\n",
+        );
+        let map = AliasAndCommand::get_alias_to_bookmark_map(&[file.path().to_str().unwrap()])
+            .ok()
+            .unwrap();
+        assert!(map.contains_key("r/rust"));
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let file =
+            temp_file("- {alias: x, description: broken, encode: false, url: https://x.com}\n");
+        let path = file.path();
+        assert!(AliasAndCommand::get_alias_to_bookmark_map(&[path.to_str().unwrap()]).is_err());
+        assert!(AliasAndCommand::get_alias_to_bookmark_map(&["does-not-exist.yml"]).is_err());
     }
 
     #[test]
@@ -87,13 +370,219 @@ mod tests {
         let aliases_and_commands = vec![
             AliasAndCommand {
                 alias: "a".to_string(),
-                command: Box::new(BookmarkCommand::new("www.example.com", "test website")),
+                additional_aliases: vec![],
+                command: Arc::new(BookmarkCommand::new("www.example.com", "test website")),
+                tags: vec![],
+                notes: None,
+                icon: None,
             },
             AliasAndCommand {
                 alias: "a".to_string(),
-                command: Box::new(BookmarkCommand::new("www.example2.com", "test2 website")),
+                additional_aliases: vec![],
+                command: Arc::new(BookmarkCommand::new("www.example2.com", "test2 website")),
+                tags: vec![],
+                notes: None,
+                icon: None,
             },
         ];
         let _ = AliasAndCommand::create_alias_to_bookmark_map(aliases_and_commands);
     }
+
+    #[test]
+    fn test_additional_aliases() {
+        let settings: Vec<YmlSettings> = serde_yaml::from_str(
+            "- {alias: yt, aliases: [youtube, tube], description: test, url: www.youtube.com}\n- {alias: g, description: test, url: www.google.com}\n",
+        )
+        .unwrap();
+        let alias_and_commands = settings.into_iter().map(AliasAndCommand::from).collect();
+        let map = AliasAndCommand::create_alias_to_bookmark_map(alias_and_commands);
+        assert_eq!(map.len(), 4);
+        assert_eq!(map["tube"].alias(), "yt");
+        assert_eq!(
+            map["youtube"].command().get_redirect_url(""),
+            "www.youtube.com".to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate alias: g")]
+    fn test_additional_alias_conflict_panics() {
+        let settings: Vec<YmlSettings> = serde_yaml::from_str(
+            "- {alias: google, aliases: [g], description: test, url: www.google.com}\n- {alias: g, description: test, url: www.google.com}\n",
+        )
+        .unwrap();
+        let alias_and_commands = settings.into_iter().map(AliasAndCommand::from).collect();
+        let _ = AliasAndCommand::create_alias_to_bookmark_map(alias_and_commands);
+    }
+
+    #[test]
+    fn test_tags() {
+        let settings: YmlSettings = serde_yaml::from_str(
+            "alias: a\ndescription: test\nurl: www.example.com\ntags: [dev, Work]\n",
+        )
+        .unwrap();
+        let alias_and_command = AliasAndCommand::from(settings);
+        assert_eq!(alias_and_command.tags(), ["dev", "Work"]);
+        assert!(alias_and_command.has_tag("work"));
+        assert!(!alias_and_command.has_tag("media"));
+    }
+
+    fn redirect_url(resolution: Option<Resolution>) -> Option<String> {
+        match resolution?.destination {
+            Destination::Redirect(url) => Some(url),
+            Destination::Post { .. } => None,
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let map = crate::tests::sample_map();
+
+        let resolution = resolve(&map, "g hello", "g").unwrap();
+        assert_eq!(resolution.alias, "g".to_string());
+        assert!(!resolution.used_default);
+        assert_eq!(
+            redirect_url(Some(resolution)),
+            Some("https://www.google.com/search?q=hello".to_string())
+        );
+
+        assert_eq!(
+            redirect_url(resolve(&map, "pi/j", "g")),
+            Some("http://pi:8096".to_string())
+        );
+
+        let resolution = resolve(&map, "unknown hello", "g").unwrap();
+        assert!(resolution.used_default);
+        assert_eq!(
+            redirect_url(Some(resolution)),
+            Some("https://www.google.com/search?q=unknown%20hello".to_string())
+        );
+
+        let resolution = resolve(&map, "pi/zzz", "g").unwrap();
+        assert_eq!(resolution.unknown_alias, Some("zzz".to_string()));
+        assert_eq!(
+            redirect_url(Some(resolution)),
+            Some("http://pi".to_string())
+        );
+
+        let resolution = resolve(&map, "g/foo", "g").unwrap();
+        assert!(resolution.used_default);
+        assert_eq!(
+            redirect_url(Some(resolution)),
+            Some("https://www.google.com/search?q=g%2Ffoo".to_string())
+        );
+
+        assert!(resolve(&map, "unknown hello", "missing").is_none());
+    }
+
+    #[test]
+    fn test_find_aliases() {
+        let map = AliasAndCommand::create_alias_to_bookmark_map_from_str(
+            "- {alias: gh, description: github, url: https://github.com}\n- {alias: g, aliases: [google], description: google, url: https://google.com}\n- {alias: yt, description: youtube, url: https://youtube.com}\n",
+        );
+        let aliases: Vec<String> = find_aliases(&map, "g")
+            .into_iter()
+            .map(|(alias, _)| alias)
+            .collect();
+        assert_eq!(aliases, vec!["g", "gh", "google"]);
+        assert_eq!(find_aliases(&map, "goo")[0].1.short_description(), "google");
+        assert!(find_aliases(&map, "x").is_empty());
+    }
+
+    #[test]
+    fn test_nested_synonyms() {
+        let map = crate::tests::sample_map();
+        let description = map["pi"].command().description();
+        assert_eq!(description, "Pi, pages|j, jelly: Jellyfin");
+        assert_eq!(
+            redirect_url(resolve(&map, "pi/jelly", "pi")),
+            Some("http://pi:8096".to_string())
+        );
+    }
+
+    #[test]
+    fn test_find_nested_aliases() {
+        let map = AliasAndCommand::create_alias_to_bookmark_map_from_str(
+            "- {alias: aoc, description: aoc, url: www.aoc.com, nested: [{alias: j, description: j, url: www.j.com, nested: [{alias: x, description: x, url: www.x.com}]}, {alias: jd, description: jd, url: www.jd.com}, {alias: k, description: k, url: www.k.com}]}\n- {alias: aocx, description: aocx, url: www.aocx.com}\n",
+        );
+        let aliases = |prefix| -> Vec<String> {
+            find_aliases(&map, prefix)
+                .into_iter()
+                .map(|(alias, _)| alias)
+                .collect()
+        };
+        assert_eq!(aliases("a"), vec!["aoc", "aocx"]);
+        assert_eq!(aliases("aoc/"), vec!["aoc/j", "aoc/jd", "aoc/k"]);
+        assert_eq!(aliases("aoc/j"), vec!["aoc/j", "aoc/jd"]);
+        assert_eq!(aliases("aoc/j/"), vec!["aoc/j/x"]);
+        assert!(aliases("aoc/z").is_empty());
+        assert!(aliases("aocx/").is_empty());
+        assert_eq!(find_aliases(&map, "aoc/k")[0].1.short_description(), "k");
+    }
+
+    #[test]
+    fn test_split_alias_and_query() {
+        let map = crate::tests::sample_map();
+        assert_eq!(split_alias_and_query(&map, "g"), ("g", "".to_string()));
+        assert_eq!(
+            split_alias_and_query(&map, "g hello world"),
+            ("g", "hello world".to_string())
+        );
+        assert_eq!(
+            split_alias_and_query(&map, "g https://example.com/a"),
+            ("g", "https://example.com/a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_split_nested_alias_path() {
+        let map = crate::tests::sample_map();
+        assert_eq!(split_alias_and_query(&map, "pi/j"), ("pi", "j".to_string()));
+        assert_eq!(
+            split_alias_and_query(&map, "pi/j hello world"),
+            ("pi", "j hello world".to_string())
+        );
+        assert_eq!(
+            split_alias_and_query(&map, "pi/a/b/ hello"),
+            ("pi", "a b hello".to_string())
+        );
+        assert_eq!(split_alias_and_query(&map, "/j"), ("/j", "".to_string()));
+    }
+
+    #[test]
+    fn test_split_path_without_nested_commands() {
+        let map = crate::tests::sample_map();
+        assert_eq!(
+            split_alias_and_query(&map, "g/foo"),
+            ("g/foo", "".to_string())
+        );
+        assert_eq!(
+            split_alias_and_query(&map, "r/rust"),
+            ("r/rust", "".to_string())
+        );
+        assert_eq!(
+            split_alias_and_query(&map, "a/b testing"),
+            ("a/b", "testing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_notes() {
+        let settings: YmlSettings = serde_yaml::from_str(
+            "alias: a\ndescription: test\nurl: www.example.com\nnotes: needs VPN\n",
+        )
+        .unwrap();
+        let alias_and_command = AliasAndCommand::from(settings);
+        assert_eq!(alias_and_command.notes(), Some("needs VPN"));
+    }
+
+    #[test]
+    fn test_icon() {
+        let settings: YmlSettings = serde_yaml::from_str(
+            "alias: a\ndescription: test\nurl: www.example.com\nicon: \"🎬\"\n",
+        )
+        .unwrap();
+        let alias_and_command = AliasAndCommand::from(settings);
+        assert_eq!(alias_and_command.icon(), Some("🎬"));
+    }
 }
@@ -0,0 +1,196 @@
+use crate::base_url::BasePath;
+use crate::branding::Branding;
+use crate::commands::DEFAULT_CONFIG_FILE;
+use rocket::figment::{
+    providers::{Env, Format, Serialized, Toml},
+    Figment,
+};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+
+pub const DEFAULT_SETTINGS_FILE: &str = "brunnylol.toml";
+const ENV_PREFIX: &str = "BRUNNYLOL_";
+const DEFAULT_ALIAS: &str = "g";
+
+/// Server settings, layered from the settings file, then `BRUNNYLOL_` environment variables
+/// (e.g. `BRUNNYLOL_DEFAULT_ALIAS`), then command line flags. Later layers win.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Config {
+    /// Paths to the commands files to load.
+    pub commands: Vec<String>,
+    pub default_alias: String,
+    pub instance_name: Option<String>,
+    pub logo_url: Option<String>,
+    pub accent_color: Option<String>,
+    /// The address to listen on. Rocket's default is used if unset.
+    pub address: Option<IpAddr>,
+    /// The port to listen on. Rocket's default is used if unset.
+    pub port: Option<u16>,
+    /// The path prefix to serve under, e.g. `/bl` to serve at `https://example.com/bl/`.
+    pub base_path: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            commands: vec![DEFAULT_CONFIG_FILE.to_string()],
+            default_alias: DEFAULT_ALIAS.to_string(),
+            instance_name: None,
+            logo_url: None,
+            accent_color: None,
+            address: None,
+            port: None,
+            base_path: None,
+        }
+    }
+}
+
+/// Settings passed on the command line. Only the flags that were passed override the other layers.
+#[derive(Serialize, Default, Clone)]
+pub struct CliConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commands: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_path: Option<String>,
+}
+
+/// Parses a `--bind` address such as `127.0.0.1:8081` or `[::1]:8081`.
+pub fn parse_bind(bind: &str) -> Result<SocketAddr, String> {
+    if bind.starts_with("unix:") {
+        return Err("listening on a unix socket is not supported".to_string());
+    }
+    bind.parse()
+        .map_err(|_| format!("{} is not an address and port, e.g. 127.0.0.1:8081", bind))
+}
+
+impl Config {
+    /// Loads the settings from `path`, or from `brunnylol.toml` if no path is given. A missing
+    /// default settings file is not an error, since every setting has a default, but a missing
+    /// file that was asked for is.
+    pub fn load(path: Option<&str>, cli: CliConfig) -> Result<Self, String> {
+        if let Some(path) = path {
+            if !Path::new(path).is_file() {
+                return Err(format!("Settings file {} does not exist", path));
+            }
+        }
+        Figment::from(Serialized::defaults(Config::default()))
+            .merge(Toml::file(path.unwrap_or(DEFAULT_SETTINGS_FILE)))
+            .merge(Env::prefixed(ENV_PREFIX))
+            .merge(Serialized::defaults(cli))
+            .extract()
+            .map_err(|e| format!("Invalid configuration: {}", e))
+    }
+
+    pub fn branding(&self) -> Result<Branding, String> {
+        Branding::new(
+            self.instance_name.as_deref(),
+            self.logo_url.as_deref(),
+            self.accent_color.as_deref(),
+        )
+    }
+
+    pub fn base_path(&self) -> Result<BasePath, String> {
+        BasePath::new(self.base_path.as_deref().unwrap_or_default())
+    }
+
+    /// Checks the settings that can't be checked while they are loaded. Returns every problem
+    /// found.
+    pub fn check(&self) -> Vec<String> {
+        vec![self.branding().err(), self.base_path().err()]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// The settings as they would be written in the settings file.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("Could not serialize configuration")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::temp_file;
+
+    #[test]
+    fn test_defaults() {
+        let config = Config::load(None, CliConfig::default()).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("brunnylol.toml");
+        assert!(Config::load(path.to_str(), CliConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_file_and_cli_layers() {
+        let file =
+            temp_file("commands = [\"a.yml\", \"b.yml\"]\ndefault_alias = \"d\"\nport = 9000\n");
+        let path = file.path();
+        let config = Config::load(
+            path.to_str(),
+            CliConfig {
+                default_alias: Some("yt".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(config.commands, vec!["a.yml", "b.yml"]);
+        assert_eq!(config.default_alias, "yt");
+        assert_eq!(config.port, Some(9000));
+    }
+
+    #[test]
+    fn test_parse_bind() {
+        assert_eq!(
+            parse_bind("127.0.0.1:8081"),
+            Ok(SocketAddr::from(([127, 0, 0, 1], 8081)))
+        );
+        assert!(parse_bind("[::1]:8081").is_ok());
+        assert!(parse_bind("127.0.0.1").is_err());
+        assert!(parse_bind("unix:/run/brunnylol.sock").is_err());
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let file = temp_file("port = \"not a port\"\n");
+        let path = file.path();
+        assert!(Config::load(path.to_str(), CliConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(Config::default().check().is_empty());
+        let config = Config {
+            accent_color: Some("not a color".to_string()),
+            base_path: Some("bl".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.check().len(), 2);
+    }
+
+    #[test]
+    fn test_to_toml() {
+        assert_eq!(
+            Config::default().to_toml(),
+            "commands = [\"commands.yml\"]\ndefault_alias = \"g\"\n"
+        );
+    }
+}
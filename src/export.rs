@@ -0,0 +1,273 @@
+use crate::{command::Command, commands::AliasAndCommand};
+use rocket::http::ContentType;
+use rocket::time::OffsetDateTime;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Browsers use %s as the query placeholder for keyword searches.
+const BROWSER_PLACEHOLDER: &str = "%s";
+
+/// The formats commands can be exported to so browsers can use them directly.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExportFormat {
+    /// Netscape bookmarks HTML, importable by every major browser. Aliases become keywords.
+    Html,
+    /// A list of search engines (name, keyword, url) as CSV.
+    Csv,
+    /// A list of search engines (name, keyword, url) as JSON.
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "html" => Ok(ExportFormat::Html),
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn content_type(&self) -> ContentType {
+        match self {
+            ExportFormat::Html => ContentType::HTML,
+            ExportFormat::Csv => ContentType::CSV,
+            ExportFormat::Json => ContentType::JSON,
+        }
+    }
+}
+
+/// A single exported command. Nested commands are exported after their parent, with the full
+/// alias path (e.g. `aoc/j`) as their keyword.
+#[derive(Serialize)]
+pub(crate) struct ExportEntry {
+    pub(crate) name: String,
+    pub(crate) keyword: String,
+    pub(crate) url: String,
+    #[serde(skip)]
+    depth: usize,
+    #[serde(skip)]
+    has_children: bool,
+}
+
+impl ExportEntry {
+    fn new(keyword: String, depth: usize, command: &dyn Command, placeholder: &str) -> Self {
+        Self {
+            name: command.short_description(),
+            url: command
+                .get_search_template(placeholder)
+                .unwrap_or_else(|| command.get_redirect_url("")),
+            keyword,
+            depth,
+            has_children: command
+                .nested_commands()
+                .is_some_and(|commands| !commands.is_empty()),
+        }
+    }
+}
+
+/// Returns the name to download an export as, e.g. `brunnylol-20240101-120000.html`.
+pub fn filename(format: ExportFormat, timestamp: OffsetDateTime) -> String {
+    format!(
+        "brunnylol-{:04}{:02}{:02}-{:02}{:02}{:02}.{}",
+        timestamp.year(),
+        u8::from(timestamp.month()),
+        timestamp.day(),
+        timestamp.hour(),
+        timestamp.minute(),
+        timestamp.second(),
+        format.extension()
+    )
+}
+
+/// Builds an entry for every command, sorted by keyword with nested commands following their
+/// parent, with `placeholder` standing in for the query in search urls.
+pub(crate) fn entries(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+    placeholder: &str,
+) -> Vec<ExportEntry> {
+    let mut entries = Vec::new();
+    crate::commands::walk(alias_to_bookmark_map, &mut |path, depth, bm| {
+        entries.push(ExportEntry::new(
+            path.to_string(),
+            depth,
+            bm.command(),
+            placeholder,
+        ))
+    });
+    entries
+}
+
+pub fn export(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+    format: ExportFormat,
+) -> String {
+    let entries = entries(alias_to_bookmark_map, BROWSER_PLACEHOLDER);
+    match format {
+        ExportFormat::Html => to_html(&entries),
+        ExportFormat::Csv => to_csv(&entries),
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&entries).expect("Could not serialize json")
+        }
+    }
+}
+
+fn to_html(entries: &[ExportEntry]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n",
+    );
+    html.push_str("<DL><p>\n");
+    let mut folders = 0;
+    for entry in entries.iter() {
+        while folders > entry.depth {
+            folders -= 1;
+            html.push_str(&format!("{}</DL><p>\n", "    ".repeat(folders + 1)));
+        }
+        html_entry(entry, &mut html);
+        if entry.has_children {
+            folders += 1;
+        }
+    }
+    while folders > 0 {
+        folders -= 1;
+        html.push_str(&format!("{}</DL><p>\n", "    ".repeat(folders + 1)));
+    }
+    html.push_str("</DL><p>\n");
+    html
+}
+
+/// Writes a link to the entry, or opens a folder holding the link if the entry has nested entries.
+/// The folder is closed once its nested entries have been written.
+fn html_entry(entry: &ExportEntry, html: &mut String) {
+    let indent = "    ".repeat(entry.depth + 1);
+    let link = format!(
+        "<DT><A HREF=\"{}\" SHORTCUTURL=\"{}\">{}</A>\n",
+        escape_html(&entry.url),
+        escape_html(&entry.keyword),
+        escape_html(&entry.name)
+    );
+    if !entry.has_children {
+        html.push_str(&format!("{}{}", indent, link));
+        return;
+    }
+
+    html.push_str(&format!(
+        "{}<DT><H3>{}</H3>\n",
+        indent,
+        escape_html(&entry.keyword)
+    ));
+    html.push_str(&format!("{}<DL><p>\n", indent));
+    html.push_str(&format!("{}    {}", indent, link));
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn to_csv(entries: &[ExportEntry]) -> String {
+    let mut csv = String::from("name,keyword,url\n");
+    for entry in entries.iter() {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            escape_csv(&entry.name),
+            escape_csv(&entry.keyword),
+            escape_csv(&entry.url)
+        ));
+    }
+    csv
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("HTML".parse(), Ok(ExportFormat::Html));
+        assert_eq!("csv".parse(), Ok(ExportFormat::Csv));
+        assert_eq!("json".parse(), Ok(ExportFormat::Json));
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_filename() {
+        let timestamp = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        assert_eq!(
+            filename(ExportFormat::Csv, timestamp),
+            "brunnylol-20231114-221320.csv"
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            export(&crate::tests::sample_map(), ExportFormat::Csv),
+            "name,keyword,url\n\
+             Search google,g,https://www.google.com/search?q=%s\n\
+             \"Pi, pages\",pi,http://pi\n\
+             Jellyfin,pi/j,http://pi:8096\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&export(&crate::tests::sample_map(), ExportFormat::Json)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+        assert_eq!(json[0]["keyword"], "g");
+        assert_eq!(json[0]["url"], "https://www.google.com/search?q=%s");
+        assert_eq!(json[2]["keyword"], "pi/j");
+    }
+
+    #[test]
+    fn test_post_command_exported_as_bookmark() {
+        let map = AliasAndCommand::create_alias_to_bookmark_map_from_str(
+            "- {alias: p, description: post, url: https://p.com, command: https://p.com/search, method: post, fields: {q: \"{}\"}}\n",
+        );
+        assert_eq!(
+            export(&map, ExportFormat::Csv),
+            "name,keyword,url\npost,p,https://p.com\n"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let html = export(&crate::tests::sample_map(), ExportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE NETSCAPE-Bookmark-file-1>"));
+        assert!(html.contains(
+            "<DT><A HREF=\"https://www.google.com/search?q=%s\" SHORTCUTURL=\"g\">Search google</A>"
+        ));
+        assert!(html.contains("<DT><H3>pi</H3>"));
+        assert!(html.contains("<DT><A HREF=\"http://pi:8096\" SHORTCUTURL=\"pi/j\">Jellyfin</A>"));
+        assert!(!html.contains("SHORTCUTURL=\"google\""));
+    }
+}
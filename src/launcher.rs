@@ -0,0 +1,162 @@
+use crate::commands::AliasAndCommand;
+use crate::export::{self, ExportEntry};
+use rocket::http::{ContentType, RawStr};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Desktop launchers that commands can be exported to.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LauncherFormat {
+    /// Raycast quicklinks, importable from Raycast's "Import Quicklinks" command.
+    Raycast,
+    /// Alfred custom searches, as `alfred://customsearch/` links that Alfred imports when opened.
+    Alfred,
+    /// Albert websearch engines, in the format of the plugin's `engines.json`.
+    Albert,
+}
+
+impl FromStr for LauncherFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "raycast" => Ok(LauncherFormat::Raycast),
+            "alfred" => Ok(LauncherFormat::Alfred),
+            "albert" => Ok(LauncherFormat::Albert),
+            _ => Err(format!("Unknown launcher format: {}", s)),
+        }
+    }
+}
+
+impl LauncherFormat {
+    /// The placeholder the launcher replaces with the query.
+    fn placeholder(&self) -> &'static str {
+        match self {
+            LauncherFormat::Raycast => "{Query}",
+            LauncherFormat::Alfred => "{query}",
+            LauncherFormat::Albert => "%s",
+        }
+    }
+
+    pub fn content_type(&self) -> ContentType {
+        match self {
+            LauncherFormat::Raycast | LauncherFormat::Albert => ContentType::JSON,
+            LauncherFormat::Alfred => ContentType::Text,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RaycastQuicklink<'a> {
+    name: &'a str,
+    link: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AlbertEngine<'a> {
+    name: &'a str,
+    trigger: String,
+    url: &'a str,
+    icon_path: &'a str,
+}
+
+pub fn launcher_export(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+    format: LauncherFormat,
+) -> String {
+    let entries = export::entries(alias_to_bookmark_map, format.placeholder());
+    match format {
+        LauncherFormat::Raycast => to_json(
+            &entries
+                .iter()
+                .map(|entry| RaycastQuicklink {
+                    name: &entry.keyword,
+                    link: &entry.url,
+                })
+                .collect::<Vec<_>>(),
+        ),
+        LauncherFormat::Alfred => to_alfred(&entries),
+        LauncherFormat::Albert => to_json(
+            &entries
+                .iter()
+                .map(|entry| AlbertEngine {
+                    name: &entry.name,
+                    trigger: format!("{} ", entry.keyword),
+                    url: &entry.url,
+                    icon_path: "",
+                })
+                .collect::<Vec<_>>(),
+        ),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).expect("Could not serialize json")
+}
+
+fn to_alfred(entries: &[ExportEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "alfred://customsearch/{}/{}/utf8/nospace/{}\n",
+                RawStr::new(&entry.name).percent_encode(),
+                RawStr::new(&entry.keyword).percent_encode(),
+                RawStr::new(&entry.url).percent_encode()
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("Raycast".parse(), Ok(LauncherFormat::Raycast));
+        assert_eq!("alfred".parse(), Ok(LauncherFormat::Alfred));
+        assert_eq!("albert".parse(), Ok(LauncherFormat::Albert));
+        assert!("spotlight".parse::<LauncherFormat>().is_err());
+    }
+
+    #[test]
+    fn test_raycast() {
+        let json: serde_json::Value = serde_json::from_str(&launcher_export(
+            &crate::tests::sample_map(),
+            LauncherFormat::Raycast,
+        ))
+        .unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+        assert_eq!(json[0]["name"], "g");
+        assert_eq!(json[0]["link"], "https://www.google.com/search?q={Query}");
+        assert_eq!(json[2]["name"], "pi/j");
+        assert_eq!(json[2]["link"], "http://pi:8096");
+    }
+
+    #[test]
+    fn test_alfred() {
+        let links = launcher_export(&crate::tests::sample_map(), LauncherFormat::Alfred);
+        assert_eq!(links.lines().count(), 3);
+        assert_eq!(
+            links.lines().next().unwrap(),
+            "alfred://customsearch/Search%20google/g/utf8/nospace/https:%2F%2Fwww.google.com%2Fsearch%3Fq%3D%7Bquery%7D"
+        );
+    }
+
+    #[test]
+    fn test_albert() {
+        let json: serde_json::Value = serde_json::from_str(&launcher_export(
+            &crate::tests::sample_map(),
+            LauncherFormat::Albert,
+        ))
+        .unwrap();
+        assert_eq!(json[0]["name"], "Search google");
+        assert_eq!(json[0]["trigger"], "g ");
+        assert_eq!(json[0]["url"], "https://www.google.com/search?q=%s");
+        assert_eq!(json[0]["iconPath"], "");
+    }
+}
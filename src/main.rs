@@ -1,91 +1,533 @@
 #[macro_use]
 extern crate rocket;
 extern crate clap;
+pub mod api;
+pub mod base_url;
+pub mod branding;
 mod command;
 pub mod commands;
+pub mod config;
+pub mod export;
+pub mod launcher;
+pub mod reload;
+pub mod suggest;
+pub mod validate;
 pub mod yml_settings;
-use command::Command;
+use base_url::{BasePath, BaseUrl};
+use branding::Branding;
+use command::Destination;
+use commands::AliasMap;
+use config::{CliConfig, Config};
+use export::ExportFormat;
+use reload::{Reloadable, Reloader};
+use rocket::figment::Figment;
+use rocket::http::{ContentType, Header};
+use rocket::response::status::BadRequest;
 use rocket::response::Redirect;
-use rocket::State;
-use rocket_dyn_templates::Template;
-use std::collections::HashMap;
-
-use clap::Arg;
-
-const DEFAULT_ALIAS: &str = "g";
-
-#[get("/help")]
-fn help(alias_to_bookmark_map: &State<HashMap<String, Box<dyn Command>>>) -> Template {
-    let mut context = HashMap::new();
-    let alias_to_description: HashMap<&String, String> = alias_to_bookmark_map
-        .iter()
-        .map(|(alias, bm)| (alias, bm.description()))
-        .collect();
-    context.insert("alias_to_description", alias_to_description);
-    Template::render("help", context)
+use rocket::serde::json::Json;
+use rocket::time::OffsetDateTime;
+use rocket::{Build, Rocket, State};
+use rocket_dyn_templates::{context, Template};
+use serde::Serialize;
+use std::net::SocketAddr;
+
+use clap::{Arg, ArgAction, ArgMatches};
+
+#[derive(Responder)]
+enum SearchResponse {
+    Redirect(Redirect),
+    PostForm(Template),
+}
+
+#[derive(Serialize)]
+struct HelpEntry<'a> {
+    alias: String,
+    additional_aliases: &'a [String],
+    description: String,
+    tags: &'a [String],
+    notes: Option<&'a str>,
+    icon: Option<&'a str>,
+    /// The commands nested under this one, at any depth, listed by their alias path.
+    nested: Vec<HelpEntry<'a>>,
+    /// Whether this command or one nested under it has the tag being filtered on.
+    #[serde(skip)]
+    tagged: bool,
+}
+
+#[get("/help?<tag>")]
+fn help(
+    tag: Option<String>,
+    alias_map: &State<AliasMap>,
+    branding: &State<Reloadable<Branding>>,
+    base_path: &State<BasePath>,
+) -> Template {
+    let alias_to_bookmark_map = alias_map.load();
+    let mut commands: Vec<HelpEntry> = Vec::new();
+    commands::walk(&alias_to_bookmark_map, &mut |path, depth, bm| {
+        let entry = HelpEntry {
+            alias: path.to_string(),
+            additional_aliases: bm.additional_aliases(),
+            description: bm.command().short_description(),
+            tags: bm.tags(),
+            notes: bm.notes(),
+            icon: bm.icon(),
+            nested: vec![],
+            tagged: tag.as_deref().is_none_or(|tag| bm.has_tag(tag)),
+        };
+        match commands.last_mut() {
+            Some(parent) if depth > 0 => {
+                parent.tagged |= entry.tagged;
+                parent.nested.push(entry);
+            }
+            _ => commands.push(entry),
+        }
+    });
+    commands.retain(|entry| entry.tagged);
+    Template::render(
+        "help",
+        context! {
+            commands,
+            tag,
+            branding: branding.load().as_ref(),
+            base_path: base_path.as_str(),
+        },
+    )
 }
 
 #[get("/")]
-fn index() -> Template {
-    let context: HashMap<String, String> = HashMap::new();
-    Template::render("index", context)
+fn index(branding: &State<Reloadable<Branding>>, base_path: &State<BasePath>) -> Template {
+    Template::render(
+        "index",
+        context! { branding: branding.load().as_ref(), base_path: base_path.as_str() },
+    )
+}
+
+#[get("/setup")]
+fn setup(
+    branding: &State<Reloadable<Branding>>,
+    base_url: BaseUrl,
+    base_path: &State<BasePath>,
+    default_alias: &State<Reloadable<String>>,
+) -> Template {
+    Template::render(
+        "setup",
+        context! {
+            branding: branding.load().as_ref(),
+            base_url: base_url.0,
+            base_path: base_path.as_str(),
+            default_alias: default_alias.load().as_str(),
+        },
+    )
+}
+
+#[get("/opensearch.xml")]
+fn opensearch(
+    branding: &State<Reloadable<Branding>>,
+    base_url: BaseUrl,
+) -> (ContentType, Template) {
+    (
+        ContentType::new("application", "opensearchdescription+xml"),
+        Template::render(
+            "opensearch",
+            context! { branding: branding.load().as_ref(), base_url: base_url.0 },
+        ),
+    )
 }
 
 #[get("/search?<q>&<default>")]
 fn redirect(
     q: String,
     default: Option<String>,
-    alias_to_bookmark_map: &State<HashMap<String, Box<dyn Command>>>,
-    default_alias: &State<String>,
-) -> Redirect {
-    let mut splitted = q.splitn(2, " ");
-    let bookmark_alias = splitted.next().unwrap();
-    let query = splitted.next().unwrap_or_default();
-
-    let redirect_url = match alias_to_bookmark_map.get(bookmark_alias) {
-        Some(bookmark) => bookmark.get_redirect_url(query),
-        None => alias_to_bookmark_map
-            .get(default.as_deref().unwrap_or(default_alias))
-            .expect(&format!(
+    alias_map: &State<AliasMap>,
+    default_alias: &State<Reloadable<String>>,
+    branding: &State<Reloadable<Branding>>,
+    base_path: &State<BasePath>,
+) -> SearchResponse {
+    let alias_to_bookmark_map = alias_map.load();
+    let default_alias = default_alias.load();
+    let default_alias = default.as_deref().unwrap_or(&default_alias);
+    let destination = commands::resolve(&alias_to_bookmark_map, &q, default_alias)
+        .unwrap_or_else(|| {
+            panic!(
                 "Default search engine alias '{}' was not found!",
                 default_alias
-            ))
-            .get_redirect_url(&q),
-    };
+            )
+        })
+        .destination;
+
+    match destination {
+        Destination::Redirect(url) => {
+            SearchResponse::Redirect(Redirect::to(base_path.prefix(&url)))
+        }
+        Destination::Post { url, fields } => SearchResponse::PostForm(Template::render(
+            "post",
+            context! {
+                url: base_path.prefix(&url),
+                fields,
+                branding: branding.load().as_ref(),
+                base_path: base_path.as_str(),
+            },
+        )),
+    }
+}
+
+#[get("/suggest?<q>")]
+fn suggestions(q: &str, alias_map: &State<AliasMap>) -> Json<suggest::Suggestions> {
+    let alias_to_bookmark_map = alias_map.load();
+    Json(suggest::suggest(&alias_to_bookmark_map, q))
+}
 
-    Redirect::to(redirect_url)
+#[derive(Responder)]
+struct ExportResponse {
+    body: (ContentType, String),
+    disposition: Header<'static>,
+}
+
+#[get("/export?<format>")]
+fn export_commands(
+    format: Option<&str>,
+    alias_map: &State<AliasMap>,
+) -> Result<ExportResponse, BadRequest<String>> {
+    let alias_to_bookmark_map = alias_map.load();
+    let format: ExportFormat = format.unwrap_or("html").parse().map_err(BadRequest)?;
+    let filename = export::filename(format, OffsetDateTime::now_utc());
+    Ok(ExportResponse {
+        body: (
+            format.content_type(),
+            export::export(&alias_to_bookmark_map, format),
+        ),
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", filename),
+        ),
+    })
 }
 
 #[launch]
 fn rocket() -> _ {
     let matches = clap::Command::new("Brunnylol")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("CONFIG")
+                .global(true)
+                .help("Path to a TOML settings file [default: brunnylol.toml, if it exists]"),
+        )
+        .arg(
+            Arg::new("print_config")
+                .long("print-config")
+                .action(ArgAction::SetTrue)
+                .help("Print the resolved settings as TOML and exit"),
+        )
+        .arg(
+            Arg::new("bind")
+                .long("bind")
+                .value_name("ADDRESS:PORT")
+                .value_parser(config::parse_bind)
+                .global(true)
+                .help("Address and port to listen on, e.g. 127.0.0.1:8081"),
+        )
+        .arg(
+            Arg::new("base_path")
+                .long("base-path")
+                .value_name("BASE_PATH")
+                .global(true)
+                .help("Path prefix to serve under, e.g. /bl"),
+        )
         .arg(
             Arg::new("commands")
                 .short('c')
                 .long("commands")
                 .value_name("COMMANDS")
-                .help("Path to a YAML file containing commands"),
+                .action(ArgAction::Append)
+                .global(true)
+                .help("Path to a YAML file containing commands. Can be passed multiple times"),
         )
         .arg(
             Arg::new("default_alias")
                 .short('a')
-                .long("default_alias")
+                .long("default-alias")
+                .alias("default_alias")
                 .value_name("DEFAULT_ALIAS")
+                .global(true)
                 .help("Default alias to use when none is provided"),
         )
+        .arg(
+            Arg::new("instance_name")
+                .long("instance-name")
+                .alias("instance_name")
+                .value_name("INSTANCE_NAME")
+                .global(true)
+                .help("Name shown in page titles and headings"),
+        )
+        .arg(
+            Arg::new("logo_url")
+                .long("logo-url")
+                .alias("logo_url")
+                .value_name("LOGO_URL")
+                .global(true)
+                .help("Url of a logo shown above page headings"),
+        )
+        .arg(
+            Arg::new("accent_color")
+                .long("accent-color")
+                .alias("accent_color")
+                .value_name("ACCENT_COLOR")
+                .global(true)
+                .help("Accent color for headings, as a hex color or color name"),
+        )
+        .subcommand(clap::Command::new("check").about(
+            "Check the settings and the commands files the server would start with, and exit",
+        ))
+        .subcommand(
+            clap::Command::new("export")
+                .about("Print every command in a format browsers can import, and exit")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .default_value("html")
+                        .value_parser(|format: &str| format.parse::<ExportFormat>())
+                        .help("Export format: html, csv, or json"),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("validate")
+                .about("Validate YAML or JSON commands files and exit")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .num_args(1..)
+                        .value_name("FILE")
+                        .help("Paths to the commands files to validate"),
+                ),
+        )
         .get_matches();
 
-    let yaml_path = matches.get_one("commands").map(|c: &String| c.as_str());
-    let default_alias = matches
-        .get_one("default_alias")
-        .map(|c: &String| c.as_str())
-        .unwrap_or(DEFAULT_ALIAS)
-        .to_string();
+    let get_string = |id: &str| matches.get_one::<String>(id).cloned();
+    let bind = matches.get_one::<SocketAddr>("bind");
+    let cli = CliConfig {
+        commands: matches
+            .get_many::<String>("commands")
+            .map(|commands| commands.cloned().collect()),
+        default_alias: get_string("default_alias"),
+        instance_name: get_string("instance_name"),
+        logo_url: get_string("logo_url"),
+        accent_color: get_string("accent_color"),
+        address: bind.map(SocketAddr::ip),
+        port: bind.map(SocketAddr::port),
+        base_path: get_string("base_path"),
+    };
+    let config_path = matches.get_one::<String>("config").cloned();
+    let config = Config::load(config_path.as_deref(), cli.clone());
+    if let Some((name, subcommand_matches)) = matches.subcommand() {
+        std::process::exit(run_subcommand(name, subcommand_matches, config));
+    }
+
+    let config = config.unwrap_or_else(|e| panic!("{}", e));
+    if matches.get_flag("print_config") {
+        print!("{}", config.to_toml());
+        std::process::exit(0);
+    }
+
+    let yaml_paths: Vec<&str> = config.commands.iter().map(String::as_str).collect();
+    let branding = config.branding().unwrap_or_else(|e| panic!("{}", e));
+    let base_path = config.base_path().unwrap_or_else(|e| panic!("{}", e));
+
+    let mut figment = rocket::Config::figment();
+    if let Some(address) = config.address {
+        figment = figment.merge(("address", address));
+    }
+    if let Some(port) = config.port {
+        figment = figment.merge(("port", port));
+    }
+
+    let alias_map = AliasMap::new(
+        commands::AliasAndCommand::get_alias_to_bookmark_map(&yaml_paths)
+            .unwrap_or_else(|report| panic!("Invalid commands files:\n{}", report)),
+    );
+    let default_alias = Reloadable::new(config.default_alias.clone());
+    let branding = Reloadable::new(branding);
+    let reloader = Reloader {
+        config_path,
+        cli,
+        config: config.clone(),
+        alias_map: alias_map.clone(),
+        default_alias: default_alias.clone(),
+        branding: branding.clone(),
+    };
+    build(figment, alias_map, default_alias, branding, base_path)
+        .attach(reload::on_hangup(reloader))
+}
+
+/// Runs one of the command line tools instead of the server, returning the exit code. Reports go
+/// to stdout, and failures that stop a tool from running go to stderr.
+fn run_subcommand(name: &str, matches: &ArgMatches, config: Result<Config, String>) -> i32 {
+    if name == "validate" {
+        let files: Vec<&str> = matches
+            .get_many("file")
+            .unwrap()
+            .map(|f: &String| f.as_str())
+            .collect();
+        let report = validate::validate_files(&files);
+        print!("{}", report);
+        return if report.is_ok() { 0 } else { 1 };
+    }
+
+    let config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("settings: {}", e);
+            return 1;
+        }
+    };
+    let yaml_paths: Vec<&str> = config.commands.iter().map(String::as_str).collect();
+    match name {
+        "check" => {
+            let errors = config.check();
+            for error in errors.iter() {
+                eprintln!("settings: {}", error);
+            }
+            let report = validate::validate_files(&yaml_paths);
+            print!("{}", report);
+            if errors.is_empty() && report.is_ok() {
+                0
+            } else {
+                1
+            }
+        }
+        "export" => {
+            let format = *matches.get_one::<ExportFormat>("format").unwrap();
+            match commands::AliasAndCommand::get_alias_to_bookmark_map(&yaml_paths) {
+                Ok(alias_to_bookmark_map) => {
+                    print!("{}", export::export(&alias_to_bookmark_map, format));
+                    0
+                }
+                Err(report) => {
+                    eprint!("{}", report);
+                    1
+                }
+            }
+        }
+        _ => unreachable!("unknown subcommand {}", name),
+    }
+}
 
-    let alias_to_bookmark_map = commands::AliasAndCommand::get_alias_to_bookmark_map(yaml_path);
-    rocket::build()
-        .manage(alias_to_bookmark_map)
+/// Builds the server around the loaded commands and settings.
+fn build(
+    figment: Figment,
+    alias_map: AliasMap,
+    default_alias: Reloadable<String>,
+    branding: Reloadable<Branding>,
+    base_path: BasePath,
+) -> Rocket<Build> {
+    rocket::custom(figment)
+        .manage(alias_map)
         .manage(default_alias)
+        .manage(branding)
+        .manage(base_path.clone())
         .attach(Template::fairing())
-        .mount("/", routes![index, help, redirect])
+        .mount(
+            base_path.mount_point(""),
+            routes![
+                index,
+                help,
+                setup,
+                opensearch,
+                redirect,
+                suggestions,
+                export_commands
+            ],
+        )
+        .mount(base_path.mount_point("/api/v1"), api::routes())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use commands::{AliasAndCommand, AliasToBookmarkMap};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use std::io::Write;
+
+    /// Commands shared by tests across modules: a search with a synonym, and a bookmark with a
+    /// nested command.
+    pub const SAMPLE_COMMANDS: &str = r#"
+- alias: g
+  aliases: [google]
+  url: https://www.google.com
+  command: https://www.google.com/search?q={}
+  description: Search google
+  tags: [search]
+- alias: pi
+  url: http://pi
+  description: "Pi, pages"
+  nested:
+    - alias: j
+      aliases: [jelly]
+      url: http://pi:8096
+      description: Jellyfin
+      tags: [media]
+      notes: Runs on the pi
+      icon: J
+"#;
+
+    pub fn sample_map() -> AliasToBookmarkMap {
+        AliasAndCommand::create_alias_to_bookmark_map_from_str(SAMPLE_COMMANDS)
+    }
+
+    /// Writes `contents` to a temporary file, which is removed when it is dropped.
+    pub fn temp_file(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().expect("Could not create temp file");
+        file.write_all(contents.as_bytes())
+            .expect("Could not write temp file");
+        file
+    }
+
+    /// Creates a client for a server with the sample commands and the default settings.
+    pub fn client() -> Client {
+        let alias_map = AliasMap::new(sample_map());
+        let figment = rocket::Config::figment().merge(("log_level", "off"));
+        let rocket = build(
+            figment,
+            alias_map,
+            Reloadable::new("g".to_string()),
+            Reloadable::new(Branding::default()),
+            BasePath::default(),
+        );
+        Client::tracked(rocket).expect("Could not build the server")
+    }
+
+    #[test]
+    fn test_search_unknown_nested_alias() {
+        let client = client();
+
+        let response = client.get("/search?q=pi/j").dispatch();
+        assert_eq!(response.status(), Status::SeeOther);
+        assert_eq!(
+            response.headers().get_one("Location"),
+            Some("http://pi:8096")
+        );
+
+        let response = client.get("/search?q=pi/zzz").dispatch();
+        assert_eq!(response.status(), Status::SeeOther);
+        assert_eq!(response.headers().get_one("Location"), Some("http://pi"));
+
+        let response = client.get("/search?q=pi+zzz+hello").dispatch();
+        assert_eq!(response.headers().get_one("Location"), Some("http://pi"));
+    }
+
+    #[test]
+    fn test_help_nested_metadata() {
+        let client = client();
+
+        let response = client.get("/help?tag=media").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body = response.into_string().unwrap();
+        assert!(body.contains("Pi, pages"));
+        assert!(body.contains("Jellyfin"));
+        assert!(body.contains("Runs on the pi"));
+        assert!(body.contains("<span class=\"icon\">J</span>"));
+        assert!(!body.contains("Search google"));
+    }
 }
@@ -0,0 +1,167 @@
+use crate::branding::Branding;
+use crate::commands::{AliasAndCommand, AliasMap};
+use crate::config::{CliConfig, Config};
+use rocket::fairing::AdHoc;
+use std::sync::{Arc, RwLock};
+
+/// State that can be replaced while the server is running. Replacing it swaps the whole value, so
+/// requests that are already running keep using the value they started with.
+pub struct Reloadable<T>(Arc<RwLock<Arc<T>>>);
+
+impl<T> Clone for Reloadable<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Reloadable<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(value))))
+    }
+
+    pub fn load(&self) -> Arc<T> {
+        self.0.read().expect("Reloadable lock poisoned").clone()
+    }
+
+    pub fn replace(&self, value: T) {
+        *self.0.write().expect("Reloadable lock poisoned") = Arc::new(value);
+    }
+}
+
+/// Reloads the settings the same way they were loaded at startup, along with the commands files
+/// they list, into the state the server is using.
+pub struct Reloader {
+    pub config_path: Option<String>,
+    pub cli: CliConfig,
+    /// The settings the server was started with.
+    pub config: Config,
+    pub alias_map: AliasMap,
+    pub default_alias: Reloadable<String>,
+    pub branding: Reloadable<Branding>,
+}
+
+impl Reloader {
+    /// Re-reads the settings and the commands files, then replaces the commands, default alias,
+    /// and branding. Nothing is replaced unless everything loads, so a broken edit leaves the
+    /// server as it was. Returns the changed settings that only take effect after a restart.
+    pub fn reload(&self) -> Result<Vec<&'static str>, String> {
+        let config = Config::load(self.config_path.as_deref(), self.cli.clone())?;
+        let branding = config.branding()?;
+        let paths: Vec<&str> = config.commands.iter().map(String::as_str).collect();
+        let alias_to_bookmark_map = AliasAndCommand::get_alias_to_bookmark_map(&paths)
+            .map_err(|report| report.to_string())?;
+
+        self.alias_map.replace(alias_to_bookmark_map);
+        self.default_alias.replace(config.default_alias.clone());
+        self.branding.replace(branding);
+
+        let mut needs_restart = Vec::new();
+        if config.address != self.config.address {
+            needs_restart.push("address");
+        }
+        if config.port != self.config.port {
+            needs_restart.push("port");
+        }
+        if config.base_path != self.config.base_path {
+            needs_restart.push("base_path");
+        }
+        Ok(needs_restart)
+    }
+}
+
+/// Reloads the settings and commands whenever the server receives SIGHUP (e.g. from systemd's
+/// `ExecReload`). Open connections are not affected.
+#[cfg(unix)]
+pub fn on_hangup(reloader: Reloader) -> AdHoc {
+    use rocket::tokio::signal::unix::{signal, SignalKind};
+
+    AdHoc::on_liftoff("Reload settings and commands on SIGHUP", |_| {
+        Box::pin(async move {
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(hangups) => hangups,
+                Err(e) => {
+                    eprintln!("Could not listen for SIGHUP: {}", e);
+                    return;
+                }
+            };
+            rocket::tokio::spawn(async move {
+                while hangups.recv().await.is_some() {
+                    match reloader.reload() {
+                        Ok(needs_restart) => {
+                            println!("Reloaded settings and commands");
+                            if !needs_restart.is_empty() {
+                                eprintln!("Restart to apply the new {}", needs_restart.join(", "));
+                            }
+                        }
+                        Err(e) => eprintln!("Not reloading:\n{}", e.trim_end()),
+                    }
+                }
+            });
+        })
+    })
+}
+
+#[cfg(not(unix))]
+pub fn on_hangup(_reloader: Reloader) -> AdHoc {
+    AdHoc::on_liftoff(
+        "Reload settings and commands on SIGHUP (unsupported)",
+        |_| Box::pin(async {}),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{sample_map, temp_file};
+
+    #[test]
+    fn test_reload() {
+        let commands =
+            temp_file("- {alias: d, description: duckduckgo, url: https://duckduckgo.com}\n");
+        let settings = temp_file(&format!(
+            "commands = [{:?}]\ndefault_alias = \"d\"\ninstance_name = \"Reloaded\"\n",
+            commands.path().to_str().unwrap()
+        ));
+        let reloader = Reloader {
+            config_path: settings.path().to_str().map(str::to_string),
+            cli: CliConfig::default(),
+            config: Config::default(),
+            alias_map: AliasMap::new(sample_map()),
+            default_alias: Reloadable::new("g".to_string()),
+            branding: Reloadable::new(Branding::default()),
+        };
+
+        assert_eq!(reloader.reload(), Ok(vec![]));
+        assert!(reloader.alias_map.load().contains_key("d"));
+        assert!(!reloader.alias_map.load().contains_key("g"));
+        assert_eq!(reloader.default_alias.load().as_str(), "d");
+        assert_eq!(reloader.branding.load().instance_name, "Reloaded");
+
+        std::fs::write(
+            settings.path(),
+            format!(
+                "commands = [{:?}]\ndefault_alias = \"d\"\nport = 9000\n",
+                commands.path().to_str().unwrap()
+            ),
+        )
+        .unwrap();
+        assert_eq!(reloader.reload(), Ok(vec!["port"]));
+
+        std::fs::write(
+            commands.path(),
+            "- {alias: x, description: broken, url: https://x.com, command: https://x.com/search}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            settings.path(),
+            format!(
+                "commands = [{:?}]\ndefault_alias = \"x\"\n",
+                commands.path().to_str().unwrap()
+            ),
+        )
+        .unwrap();
+        assert!(reloader.reload().is_err());
+        assert!(reloader.alias_map.load().contains_key("d"));
+        assert_eq!(reloader.default_alias.load().as_str(), "d");
+    }
+}
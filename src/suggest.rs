@@ -0,0 +1,83 @@
+use crate::commands::{self, AliasAndCommand};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Search suggestions in the OpenSearch suggestions format: the query, the completions, their
+/// descriptions, and the urls they go to.
+pub type Suggestions = (String, Vec<String>, Vec<String>, Vec<String>);
+
+/// Suggests aliases while the first word of a search is being typed, including nested aliases
+/// after `<alias>/`. Once an alias has been chosen (the search contains a space), there is nothing
+/// left to suggest.
+pub fn suggest(
+    alias_to_bookmark_map: &HashMap<String, Arc<AliasAndCommand>>,
+    q: &str,
+) -> Suggestions {
+    let mut completions = Vec::new();
+    let mut descriptions = Vec::new();
+    let mut urls = Vec::new();
+    if !q.is_empty() && !q.contains(' ') {
+        for (alias, command) in commands::find_aliases(alias_to_bookmark_map, q)
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+        {
+            completions.push(alias);
+            descriptions.push(command.short_description());
+            urls.push(command.get_redirect_url(""));
+        }
+    }
+    (q.to_string(), completions, descriptions, urls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample_map;
+
+    #[test]
+    fn test_suggest_aliases() {
+        assert_eq!(
+            suggest(&sample_map(), "g"),
+            (
+                "g".to_string(),
+                vec!["g".to_string(), "google".to_string()],
+                vec!["Search google".to_string(), "Search google".to_string()],
+                vec![
+                    "https://www.google.com".to_string(),
+                    "https://www.google.com".to_string()
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_suggest_nested_aliases() {
+        assert_eq!(
+            suggest(&sample_map(), "pi/"),
+            (
+                "pi/".to_string(),
+                vec!["pi/j".to_string(), "pi/jelly".to_string()],
+                vec!["Jellyfin".to_string(), "Jellyfin".to_string()],
+                vec!["http://pi:8096".to_string(), "http://pi:8096".to_string()],
+            )
+        );
+        assert_eq!(
+            suggest(&sample_map(), "pi/jel").1,
+            vec!["pi/jelly".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_no_suggestions_after_alias() {
+        assert_eq!(
+            suggest(&sample_map(), "g rust"),
+            ("g rust".to_string(), vec![], vec![], vec![])
+        );
+        assert_eq!(
+            suggest(&sample_map(), ""),
+            ("".to_string(), vec![], vec![], vec![])
+        );
+    }
+}
@@ -0,0 +1,393 @@
+use crate::command::PLACEHOLDER;
+use crate::yml_settings::{HttpMethod, YmlSettings};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+const ALLOWED_URL_PREFIXES: [&str; 3] = ["http://", "https://", "/"];
+const RESERVED_ALIAS_CHARACTERS: [char; 1] = ['/'];
+
+/// A single problem found in a commands file. `location` is the alias path of the command the
+/// problem was found in (e.g. `aoc/j`), or the file itself for problems that affect the whole file.
+#[derive(Debug, PartialEq)]
+pub struct ValidationError {
+    location: String,
+    message: String,
+    /// Whether the problem only fails validation. Commands with lint problems can still be loaded,
+    /// so the server starts with them and only logs the problem.
+    lint: bool,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// The result of validating a commands file. Only errors make the file invalid; warnings point
+/// out likely mistakes, such as several aliases that go to the same place.
+#[derive(Debug)]
+pub struct ValidationReport {
+    path: String,
+    errors: Vec<ValidationError>,
+    warnings: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Whether commands can be built from the files, i.e. every error is a lint problem.
+    pub fn can_load(&self) -> bool {
+        self.errors.iter().all(|error| error.lint)
+    }
+
+    pub fn lints(&self) -> impl Iterator<Item = &ValidationError> {
+        self.errors.iter().filter(|error| error.lint)
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_ok() {
+            writeln!(f, "{}: OK", self.path)?;
+        } else {
+            writeln!(f, "{}: {} error(s)", self.path, self.errors.len())?;
+        }
+        for error in self.errors.iter() {
+            writeln!(f, "  - error: {}", error)?;
+        }
+        for warning in self.warnings.iter() {
+            writeln!(f, "  - warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validates one or more YAML (or JSON) commands files without panicking, collecting every problem
+/// found. The files are checked together, so an alias defined in two files is a duplicate.
+pub fn validate_files(paths: &[&str]) -> ValidationReport {
+    load_files(paths).1
+}
+
+/// Reads and parses the commands files once, returning their settings along with the validation
+/// report for them. The settings are only safe to build commands from if the report can load.
+pub fn load_files(paths: &[&str]) -> (Vec<YmlSettings>, ValidationReport) {
+    let mut errors = Vec::new();
+    let mut settings = Vec::new();
+    for path in paths.iter() {
+        match std::fs::read_to_string(path) {
+            Ok(yml) => match serde_yaml::from_str::<Vec<YmlSettings>>(&yml) {
+                Ok(file_settings) => settings.extend(file_settings),
+                Err(e) => errors.push(error(path, &format!("invalid yaml configuration: {}", e))),
+            },
+            Err(e) => errors.push(error(path, &format!("could not read file: {}", e))),
+        }
+    }
+    errors.extend(validate_settings(&settings));
+    let warnings = find_duplicate_urls(&settings);
+    let report = ValidationReport {
+        path: paths.join(", "),
+        errors,
+        warnings,
+    };
+    (settings, report)
+}
+
+pub fn validate_settings(settings: &[YmlSettings]) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    validate_level(settings, "", &mut errors);
+    errors
+}
+
+/// Finds groups of aliases that share a url or a command template, ignoring trailing slashes.
+pub fn find_duplicate_urls(settings: &[YmlSettings]) -> Vec<ValidationError> {
+    let mut urls: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut templates: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    collect_urls(settings, "", &mut urls, &mut templates);
+
+    let duplicates = |map: BTreeMap<String, Vec<String>>, kind: &str| {
+        map.into_iter()
+            .filter(|(_, locations)| locations.len() > 1)
+            .map(|(url, locations)| error(&locations.join(", "), &format!("same {} {}", kind, url)))
+            .collect::<Vec<_>>()
+    };
+    let mut warnings = duplicates(urls, "url");
+    warnings.extend(duplicates(templates, "command"));
+    warnings
+}
+
+fn collect_urls(
+    settings: &[YmlSettings],
+    prefix: &str,
+    urls: &mut BTreeMap<String, Vec<String>>,
+    templates: &mut BTreeMap<String, Vec<String>>,
+) {
+    for setting in settings.iter() {
+        let location = format!("{}{}", prefix, setting.alias);
+        urls.entry(setting.url.trim_end_matches('/').to_string())
+            .or_default()
+            .push(location.clone());
+        if let Some(command) = &setting.command {
+            templates
+                .entry(command.trim_end_matches('/').to_string())
+                .or_default()
+                .push(location.clone());
+        }
+        if let Some(nested) = &setting.nested {
+            collect_urls(nested, &format!("{}/", location), urls, templates);
+        }
+    }
+}
+
+fn validate_level(settings: &[YmlSettings], prefix: &str, errors: &mut Vec<ValidationError>) {
+    let mut seen = HashSet::new();
+    for setting in settings.iter() {
+        let location = format!("{}{}", prefix, setting.alias);
+        for alias in aliases(setting) {
+            if !seen.insert(alias) {
+                errors.push(error(&location, &format!("duplicate alias {}", alias)));
+            }
+        }
+        validate_setting(setting, &location, errors);
+    }
+}
+
+fn aliases(setting: &YmlSettings) -> impl Iterator<Item = &str> {
+    std::iter::once(setting.alias.as_str())
+        .chain(setting.aliases.iter().flatten().map(|alias| alias.as_str()))
+}
+
+fn validate_setting(setting: &YmlSettings, location: &str, errors: &mut Vec<ValidationError>) {
+    for alias in aliases(setting) {
+        validate_alias(alias, location, errors);
+    }
+
+    validate_url(&setting.url, location, errors);
+    if let Some(command) = &setting.command {
+        validate_url(command, location, errors);
+    }
+
+    let method = setting.method.as_ref().unwrap_or(&HttpMethod::Get);
+    match (
+        method,
+        &setting.command,
+        &setting.encode,
+        &setting.nested,
+        &setting.fields,
+    ) {
+        (HttpMethod::Get, None, None, None, None) => (),
+        (HttpMethod::Get, Some(command), _, None, None) => {
+            if !command.contains(PLACEHOLDER) {
+                errors.push(error(
+                    location,
+                    &format!("command {} does not contain {}", command, PLACEHOLDER),
+                ));
+            }
+        }
+        (HttpMethod::Get, None, None, Some(nested), None) => {
+            validate_level(nested, &format!("{}/", location), errors);
+        }
+        (HttpMethod::Post, Some(_), None, None, Some(_)) => (),
+        (HttpMethod::Post, _, _, _, _) => errors.push(error(
+            location,
+            "post commands need a command and fields, and cannot use encode or nested",
+        )),
+        _ => errors.push(error(
+            location,
+            "invalid combination of command, encode, nested, and fields",
+        )),
+    }
+}
+
+fn validate_alias(alias: &str, location: &str, errors: &mut Vec<ValidationError>) {
+    if alias.is_empty() {
+        errors.push(lint(location, "alias is empty"));
+    }
+    if alias.chars().any(char::is_whitespace) {
+        errors.push(lint(
+            location,
+            &format!("alias {} contains whitespace", alias),
+        ));
+    }
+    if let Some(c) = alias
+        .chars()
+        .find(|c| RESERVED_ALIAS_CHARACTERS.contains(c))
+    {
+        errors.push(lint(
+            location,
+            &format!("alias {} contains reserved character '{}'", alias, c),
+        ));
+    }
+}
+
+fn validate_url(url: &str, location: &str, errors: &mut Vec<ValidationError>) {
+    if !ALLOWED_URL_PREFIXES
+        .iter()
+        .any(|prefix| url.starts_with(prefix))
+    {
+        errors.push(lint(
+            location,
+            &format!("url {} must start with http://, https://, or /", url),
+        ));
+    }
+}
+
+fn error(location: &str, message: &str) -> ValidationError {
+    ValidationError {
+        location: location.to_string(),
+        message: message.to_string(),
+        lint: false,
+    }
+}
+
+/// A problem that doesn't stop the commands from being built, such as a url that browsers may not
+/// follow.
+fn lint(location: &str, message: &str) -> ValidationError {
+    ValidationError {
+        lint: true,
+        ..error(location, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::temp_file;
+
+    fn validate_yml(yml: &str) -> Vec<String> {
+        let settings: Vec<YmlSettings> = serde_yaml::from_str(yml).unwrap();
+        validate_settings(&settings)
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_default_file_is_valid() {
+        let report = validate_files(&["commands.yml"]);
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let report = validate_files(&["commands.yml", "does-not-exist.yml"]);
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_across_files() {
+        let file = temp_file("- {alias: g, description: test, url: https://g.com}\n");
+        let path = file.path();
+        let report = validate_files(&["commands.yml", path.to_str().unwrap()]);
+        assert_eq!(
+            report
+                .errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            vec!["g: duplicate alias g"]
+        );
+    }
+
+    #[test]
+    fn test_lints_can_load() {
+        let file = temp_file(
+            "- {alias: r/rust, description: rust, url: www.rust-lang.org}\n- {alias: a, description: a, url: https://a.com, command: https://a.com/search}\n",
+        );
+        let report = validate_files(&[file.path().to_str().unwrap()]);
+        assert!(!report.is_ok());
+        assert!(!report.can_load());
+
+        let file = temp_file("- {alias: r/rust, description: rust, url: www.rust-lang.org}\n");
+        let report = validate_files(&[file.path().to_str().unwrap()]);
+        assert!(!report.is_ok());
+        assert!(report.can_load());
+        assert_eq!(report.lints().count(), 2);
+    }
+
+    #[test]
+    fn test_json_file() {
+        let settings: Vec<YmlSettings> = serde_yaml::from_str(
+            r#"[{"alias": "g", "description": "google", "url": "https://google.com"}]"#,
+        )
+        .unwrap();
+        assert_eq!(validate_settings(&settings), vec![]);
+    }
+
+    #[test]
+    fn test_duplicate_alias() {
+        let errors = validate_yml(
+            "- {alias: a, description: a, url: https://a.com}\n- {alias: a, description: b, url: https://b.com}\n",
+        );
+        assert_eq!(errors, vec!["a: duplicate alias a"]);
+    }
+
+    #[test]
+    fn test_duplicate_additional_alias() {
+        let errors = validate_yml(
+            "- {alias: g, description: a, url: https://a.com}\n- {alias: google, aliases: [goog, g], description: b, url: https://b.com}\n",
+        );
+        assert_eq!(errors, vec!["google: duplicate alias g"]);
+    }
+
+    #[test]
+    fn test_nested_errors() {
+        let errors = validate_yml(
+            "- alias: a\n  description: a\n  url: https://a.com\n  nested:\n    - {alias: b, description: b, url: https://b.com, command: https://b.com/search}\n    - {alias: b, description: b, url: ftp://b.com}\n",
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "a/b: command https://b.com/search does not contain {}",
+                "a/b: duplicate alias b",
+                "a/b: url ftp://b.com must start with http://, https://, or /",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_urls() {
+        let settings: Vec<YmlSettings> = serde_yaml::from_str(
+            "- {alias: a, description: a, url: https://a.com/, command: \"https://a.com/?q={}\"}\n- alias: b\n  description: b\n  url: https://b.com\n  nested:\n    - {alias: c, description: c, url: https://a.com, command: \"https://a.com/?q={}\"}\n",
+        )
+        .unwrap();
+        let warnings: Vec<String> = find_duplicate_urls(&settings)
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "a, b/c: same url https://a.com",
+                "a, b/c: same command https://a.com/?q={}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reserved_alias() {
+        let errors = validate_yml("- {alias: \"a/b c\", description: a, url: https://a.com}\n");
+        assert_eq!(
+            errors,
+            vec![
+                "a/b c: alias a/b c contains whitespace",
+                "a/b c: alias a/b c contains reserved character '/'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_combination() {
+        let errors = validate_yml(
+            "- {alias: a, description: a, url: https://a.com, encode: false}\n- {alias: b, description: b, url: https://b.com, method: post}\n",
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "a: invalid combination of command, encode, nested, and fields",
+                "b: post commands need a command and fields, and cannot use encode or nested",
+            ]
+        );
+    }
+}
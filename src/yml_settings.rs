@@ -1,13 +1,30 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The HTTP method a command uses to send the query to its target.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Post,
+}
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct YmlSettings {
     pub alias: String,
+    pub aliases: Option<Vec<String>>,
     pub description: String,
     pub url: String,
     pub command: Option<String>,
     pub encode: Option<bool>,
     pub nested: Option<Vec<YmlSettings>>,
+    pub method: Option<HttpMethod>,
+    pub fields: Option<HashMap<String, String>>,
+    pub fragment: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub notes: Option<String>,
+    pub icon: Option<String>,
 }
 
 #[cfg(test)]
@@ -18,15 +35,30 @@ mod tests {
     fn test_serde() {
         let settings = YmlSettings {
             alias: "g".to_string(),
+            aliases: None,
             description: "test".to_string(),
             url: "hi".to_string(),
             command: None,
             encode: None,
             nested: None,
+            method: None,
+            fields: None,
+            fragment: None,
+            tags: None,
+            notes: None,
+            icon: None,
         };
 
         let yml = serde_yaml::to_string(&settings).unwrap();
         let deserde: YmlSettings = serde_yaml::from_str(&yml).unwrap();
         assert_eq!(settings, deserde);
     }
+
+    #[test]
+    fn test_post_method() {
+        let yml = "alias: p\ndescription: test\nurl: hi\ncommand: hi/search\nmethod: post\nfields:\n  q: \"{}\"\n";
+        let settings: YmlSettings = serde_yaml::from_str(yml).unwrap();
+        assert_eq!(settings.method, Some(HttpMethod::Post));
+        assert_eq!(settings.fields.unwrap().get("q"), Some(&"{}".to_string()));
+    }
 }